@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use anyhow::{anyhow, Result};
+
+use crate::ai::git::{git_config, git_dir};
+
+const EDIT_FILE_NAME: &str = "GITBUDDY_EDITMSG";
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+/// candidates git tries for `core.commentChar=auto`
+const AUTO_COMMENT_CHARS: &str = "#;@!$%^&|:";
+
+/// How the edited message is cleaned up, see `commit.cleanup` in git-config(1)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Cleanup {
+    Strip,
+    Whitespace,
+    Verbatim,
+    Scissors,
+}
+
+impl Cleanup {
+    fn from_config() -> Self {
+        match git_config("commit.cleanup").as_deref() {
+            Some("whitespace") => Cleanup::Whitespace,
+            Some("verbatim") => Cleanup::Verbatim,
+            Some("scissors") => Cleanup::Scissors,
+            // `default` strips when the message is edited, as it is here
+            _ => Cleanup::Strip,
+        }
+    }
+}
+
+/// Opens the message in the editor git would use, with the staged files listed as comments,
+/// and returns the edited text cleaned up like `git commit` would
+pub fn edit(message: &str, filenames: &[String]) -> Result<String> {
+    let path = git_dir()
+        .ok_or_else(|| anyhow!("Not git directory"))?
+        .join(EDIT_FILE_NAME);
+
+    let cleanup = Cleanup::from_config();
+    let comment_char = comment_char(message);
+    fs::write(&path, template(message, filenames, comment_char, cleanup))?;
+
+    let editor = git_editor();
+    let status = run_editor(&editor, &path)?;
+    if !status.success() {
+        return Err(anyhow!("Editor `{}` exited with {}", editor, status));
+    }
+
+    let edited = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    Ok(clean(&edited, comment_char, cleanup))
+}
+
+/// Removes the comment lines and everything below a scissors line, as `git commit` does for edited messages
+pub fn strip_comments(text: &str) -> String {
+    let comment_char = match git_config("core.commentChar").as_deref() {
+        Some("auto") | None => '#',
+        Some(value) => value.chars().next().unwrap_or('#'),
+    };
+    let text = clean(text, comment_char, Cleanup::Scissors);
+    clean(&text, comment_char, Cleanup::Strip)
+}
+
+/// The editor git itself would use: `GIT_EDITOR`, `core.editor`, `VISUAL`, `EDITOR`, then `vi`
+fn git_editor() -> String {
+    match Command::new("git").args(["var", "GIT_EDITOR"]).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        _ => "vi".to_string(),
+    }
+}
+
+/// Runs the editor command on the file. The command is split into words so it may carry arguments,
+/// and on Windows it goes through `cmd /C` when it is no program of its own, like a `.cmd` script
+fn run_editor(editor: &str, path: &Path) -> Result<ExitStatus> {
+    let words = split_command(editor);
+    let (program, args) = words.split_first().ok_or_else(|| anyhow!("No editor configured"))?;
+    match Command::new(program).args(args).arg(path).status() {
+        Err(_) if cfg!(windows) => Ok(Command::new("cmd").arg("/C").arg(editor).arg(path).status()?),
+        result => Ok(result?),
+    }
+}
+
+/// Splits a command line at whitespace outside of single or double quotes. Backslashes are kept
+/// as they are, they separate the directories of Windows paths.
+fn split_command(command: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    for c in command.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            None => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// `core.commentChar`, picking one the message does not start a line with for `auto`
+fn comment_char(message: &str) -> char {
+    match git_config("core.commentChar").as_deref() {
+        Some("auto") => AUTO_COMMENT_CHARS
+            .chars()
+            .find(|c| !message.lines().any(|line| line.trim_start().starts_with(*c)))
+            .unwrap_or('#'),
+        Some(value) => value.chars().next().unwrap_or('#'),
+        None => '#',
+    }
+}
+
+fn template(message: &str, filenames: &[String], comment_char: char, cleanup: Cleanup) -> String {
+    let mut content = format!("{}\n\n", message.trim());
+    let hint = match cleanup {
+        Cleanup::Strip => format!(
+            "Lines starting with '{}' will be ignored, and an empty message aborts the commit.",
+            comment_char
+        ),
+        Cleanup::Scissors => {
+            content.push_str(&format!("{} {}\n", comment_char, SCISSORS));
+            "Do not modify or remove the line above.\nEverything below it will be ignored.".to_string()
+        }
+        Cleanup::Whitespace | Cleanup::Verbatim => format!(
+            "Lines starting with '{}' will be kept; you may remove them yourself if you want to.\n\
+             An empty message aborts the commit.",
+            comment_char
+        ),
+    };
+
+    let mut comments: Vec<String> = hint.lines().map(|l| l.to_string()).collect();
+    comments.push("".to_string());
+    comments.push("Changes to be committed:".to_string());
+    comments.extend(filenames.iter().map(|f| format!("\t{}", f)));
+    for line in comments {
+        if line.is_empty() || line.starts_with('\t') {
+            content.push_str(&format!("{}{}\n", comment_char, line));
+        } else {
+            content.push_str(&format!("{} {}\n", comment_char, line));
+        }
+    }
+    content
+}
+
+fn clean(text: &str, comment_char: char, cleanup: Cleanup) -> String {
+    let text = match cleanup {
+        Cleanup::Verbatim => return text.to_string(),
+        Cleanup::Scissors => {
+            let scissors = format!("{} {}", comment_char, SCISSORS);
+            match text.lines().position(|line| line == scissors) {
+                Some(i) => text.lines().take(i).collect::<Vec<_>>().join("\n"),
+                None => text.to_string(),
+            }
+        }
+        Cleanup::Strip => text
+            .lines()
+            .filter(|line| !line.starts_with(comment_char))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Cleanup::Whitespace => text.to_string(),
+    };
+
+    // strip trailing whitespace and collapse consecutive empty lines like git-stripspace
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(|line| line.trim_end()) {
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        let files = vec!["src/main.rs".to_string()];
+        let content = template("feat: add login\n\nbody", &files, '#', Cleanup::Strip);
+        assert!(content.contains("#\tsrc/main.rs"));
+        assert_eq!(clean(&content, '#', Cleanup::Strip), "feat: add login\n\nbody");
+
+        let content = template("feat: add login", &files, ';', Cleanup::Scissors);
+        assert_eq!(clean(&content, ';', Cleanup::Scissors), "feat: add login");
+
+        let edited = "fix: typo  \n\n\n# keep me\n";
+        assert_eq!(clean(edited, '#', Cleanup::Whitespace), "fix: typo\n\n# keep me");
+        assert_eq!(clean("# all comments\n", '#', Cleanup::Strip), "");
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command("vim"), vec!["vim"]);
+        assert_eq!(split_command("code --wait"), vec!["code", "--wait"]);
+        assert_eq!(
+            split_command(r#""C:\Program Files\Notepad++\notepad++.exe" -multiInst"#),
+            vec![r"C:\Program Files\Notepad++\notepad++.exe", "-multiInst"]
+        );
+        assert_eq!(split_command("emacs -nw '-f' ''"), vec!["emacs", "-nw", "-f", ""]);
+    }
+}
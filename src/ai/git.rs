@@ -1,4 +1,6 @@
-use std::process::Command;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 pub fn git_stage_filenames() -> Vec<String> {
     let output = Command::new("git")
@@ -30,10 +32,7 @@ pub fn git_stage_filenames() -> Vec<String> {
 }
 
 pub fn git_stage_diff() -> String {
-    let exclude_path: Vec<String> = ignore_filenames()
-        .iter()
-        .map(|path| format!(":(exclude){}", path))
-        .collect();
+    let exclude_path = exclude_pathspecs();
 
     let mut command = Command::new("git");
     command.args(&[
@@ -56,6 +55,646 @@ pub fn git_stage_diff() -> String {
     String::from_utf8(output.stdout).unwrap()
 }
 
+/// Kind of change applied to a staged file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    /// Renamed from another path, with the similarity index reported by git
+    Renamed {
+        from: String,
+        similarity: u8,
+    },
+    /// Copied from another path, with the similarity index reported by git
+    Copied {
+        from: String,
+        similarity: u8,
+    },
+    /// Only the file mode changed (e.g. the executable bit)
+    ModeChanged {
+        old_mode: String,
+        new_mode: String,
+    },
+    TypeChanged,
+}
+
+/// A staged file together with its line statistics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Added lines, `None` for binary files
+    pub added: Option<u32>,
+    /// Deleted lines, `None` for binary files
+    pub deleted: Option<u32>,
+}
+
+/// Collects the staged changes with rename detection and per-file line statistics.
+pub fn git_stage_changes() -> Vec<FileChange> {
+    let name_status = git_diff_cached(&["-M", "--name-status"]);
+    let numstat = git_diff_cached(&["-M", "--numstat", "--summary"]);
+
+    parse_changes(&name_status, &numstat)
+}
+
+/// Collects the changes between two commits, like `git diff <from> <to>`.
+pub fn git_range_changes(from: &str, to: &str) -> Vec<FileChange> {
+    let name_status = git_diff(&[from, to, "-M", "--name-status"]);
+    let numstat = git_diff(&[from, to, "-M", "--numstat", "--summary"]);
+
+    parse_changes(&name_status, &numstat)
+}
+
+/// Returns the diff between two commits.
+pub fn git_range_diff(from: &str, to: &str) -> String {
+    git_filtered_diff(&["diff", from, to], &[])
+}
+
+/// Returns the complete staged diff as a patch `git apply` accepts, binary files and ignored files included.
+pub fn git_stage_patch() -> String {
+    git_diff_cached(&[
+        "--binary",
+        "--no-color",
+        "--no-renames",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ])
+}
+
+/// Collects the uncommitted changes of tracked files under the pathspecs, staged or not.
+pub fn git_worktree_changes(paths: &[String]) -> Vec<FileChange> {
+    let mut name_status = vec!["HEAD", "-M", "--name-status", "--"];
+    let mut numstat = vec!["HEAD", "-M", "--numstat", "--summary", "--"];
+    name_status.extend(paths.iter().map(|path| path.as_str()));
+    numstat.extend(paths.iter().map(|path| path.as_str()));
+
+    parse_changes(&git_diff(&name_status), &git_diff(&numstat))
+}
+
+/// Returns the diff of the uncommitted changes of tracked files under the pathspecs, staged or not.
+pub fn git_worktree_diff(paths: &[String]) -> String {
+    git_filtered_diff(&["diff", "HEAD"], paths)
+}
+
+/// Returns the untracked files under the pathspecs that are not ignored.
+pub fn git_untracked_files(paths: &[String]) -> Vec<String> {
+    let output = Command::new("git")
+        .args(["ls-files", "--others", "--exclude-standard", "--"])
+        .args(paths)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The tree of an empty repository, the base to diff a root commit against
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Collects what `git commit --amend` would commit: the changes of HEAD together with the staged ones.
+pub fn git_amend_changes() -> Vec<FileChange> {
+    let base = git_rev_parse("HEAD^").unwrap_or(EMPTY_TREE.to_string());
+    let name_status = git_diff_cached(&[&base, "-M", "--name-status"]);
+    let numstat = git_diff_cached(&[&base, "-M", "--numstat", "--summary"]);
+
+    parse_changes(&name_status, &numstat)
+}
+
+/// Returns the diff `git commit --amend` would commit, the changes of HEAD together with the staged ones.
+pub fn git_amend_diff() -> String {
+    let base = git_rev_parse("HEAD^").unwrap_or(EMPTY_TREE.to_string());
+    git_filtered_diff(&["diff", "--cached", &base], &[])
+}
+
+/// Collects the changes a single commit introduced.
+pub fn git_commit_changes(rev: &str) -> Vec<FileChange> {
+    let name_status = git_show(rev, &["-M", "--name-status"]);
+    let numstat = git_show(rev, &["-M", "--numstat", "--summary"]);
+
+    parse_changes(&name_status, &numstat)
+}
+
+/// Returns the diff a single commit introduced.
+pub fn git_commit_diff(rev: &str) -> String {
+    git_filtered_diff(&["show", "--format=", rev], &[])
+}
+
+/// Runs a `git diff` or `git show` command limited to the pathspecs and without the lock files and build output
+/// of `ignore_filenames`. Returns an empty string when git fails.
+fn git_filtered_diff(command: &[&str], paths: &[String]) -> String {
+    let output = Command::new("git")
+        .args(command)
+        .args(["--no-ext-diff", "--diff-algorithm=minimal", "--"])
+        .args(paths)
+        .args(exclude_pathspecs())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        _ => "".to_string(),
+    }
+}
+
+fn git_show(rev: &str, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["show", "--no-ext-diff", "--format=", rev])
+        .args(args)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        _ => "".to_string(),
+    }
+}
+
+fn git_diff_cached(args: &[&str]) -> String {
+    let mut cached = vec!["--cached"];
+    cached.extend_from_slice(args);
+    git_diff(&cached)
+}
+
+fn git_diff(args: &[&str]) -> String {
+    let output = Command::new("git").args(["diff", "--no-ext-diff"]).args(args).output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        _ => "".to_string(),
+    }
+}
+
+/// Combines `--name-status` and `--numstat --summary` output. Both list the files in the same order,
+/// so line statistics are matched by position rather than by (possibly abbreviated) path.
+fn parse_changes(name_status: &str, numstat: &str) -> Vec<FileChange> {
+    let mut stats = vec![];
+    let mut mode_changes = vec![];
+    for line in numstat.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix("mode change ") {
+            // e.g. "mode change 100644 => 100755 script.sh"
+            let mut parts = rest.splitn(4, ' ');
+            if let (Some(old_mode), Some("=>"), Some(new_mode), Some(path)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            {
+                mode_changes.push((path.to_string(), old_mode.to_string(), new_mode.to_string()));
+            }
+            continue;
+        }
+
+        let mut parts = line.split('\t');
+        if let (Some(added), Some(deleted), Some(_)) = (parts.next(), parts.next(), parts.next()) {
+            stats.push((added.parse().ok(), deleted.parse().ok()));
+        }
+    }
+
+    name_status
+        .lines()
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let status = fields.first()?;
+            let similarity = status.get(1..).and_then(|s| s.parse().ok()).unwrap_or(100);
+
+            let (path, kind) = match status.chars().next()? {
+                'A' => (fields.get(1)?.to_string(), ChangeKind::Added),
+                'D' => (fields.get(1)?.to_string(), ChangeKind::Deleted),
+                'T' => (fields.get(1)?.to_string(), ChangeKind::TypeChanged),
+                'R' => (
+                    fields.get(2)?.to_string(),
+                    ChangeKind::Renamed {
+                        from: fields.get(1)?.to_string(),
+                        similarity,
+                    },
+                ),
+                'C' => (
+                    fields.get(2)?.to_string(),
+                    ChangeKind::Copied {
+                        from: fields.get(1)?.to_string(),
+                        similarity,
+                    },
+                ),
+                _ => {
+                    let path = fields.get(1)?.to_string();
+                    let kind = match mode_changes.iter().find(|(p, _, _)| *p == path) {
+                        Some((_, old_mode, new_mode)) => ChangeKind::ModeChanged {
+                            old_mode: old_mode.clone(),
+                            new_mode: new_mode.clone(),
+                        },
+                        None => ChangeKind::Modified,
+                    };
+                    (path, kind)
+                }
+            };
+
+            let (added, deleted) = stats.get(index).copied().unwrap_or((None, None));
+            Some(FileChange {
+                path,
+                kind,
+                added,
+                deleted,
+            })
+        })
+        .collect()
+}
+
+/// Renders the staged changes as a compact, human readable summary for the prompt.
+pub fn change_summary(changes: &[FileChange]) -> String {
+    let total_added: u32 = changes.iter().filter_map(|c| c.added).sum();
+    let total_deleted: u32 = changes.iter().filter_map(|c| c.deleted).sum();
+
+    let mut summary = format!(
+        "Changed files ({} files, +{} -{}):\n",
+        changes.len(),
+        total_added,
+        total_deleted
+    );
+
+    for change in changes {
+        let lines = match (change.added, change.deleted) {
+            (Some(added), Some(deleted)) => format!("+{} -{}", added, deleted),
+            _ => "binary".to_string(),
+        };
+
+        let line = match &change.kind {
+            ChangeKind::Added => format!("added     {} ({})", change.path, lines),
+            ChangeKind::Modified => format!("modified  {} ({})", change.path, lines),
+            ChangeKind::Deleted => format!("deleted   {} ({})", change.path, lines),
+            ChangeKind::TypeChanged => format!("typechange {} ({})", change.path, lines),
+            ChangeKind::Renamed { from, similarity } => {
+                format!(
+                    "renamed   {} -> {} ({}% similar, {})",
+                    from, change.path, similarity, lines
+                )
+            }
+            ChangeKind::Copied { from, similarity } => {
+                format!(
+                    "copied    {} -> {} ({}% similar, {})",
+                    from, change.path, similarity, lines
+                )
+            }
+            ChangeKind::ModeChanged { old_mode, new_mode } => {
+                format!("mode      {} ({} -> {}, {})", change.path, old_mode, new_mode, lines)
+            }
+        };
+
+        summary.push_str("  ");
+        summary.push_str(&line);
+        summary.push('\n');
+    }
+
+    summary
+}
+
+/// Returns the root directory of the current repository's working tree.
+pub fn git_toplevel() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Returns the path of the `.git` directory of the current repository.
+pub fn git_dir() -> Option<PathBuf> {
+    let output = Command::new("git").args(["rev-parse", "--git-dir"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Returns the hooks directory of the current repository, honoring `core.hooksPath`.
+pub fn git_hooks_dir() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Returns a git config value, `None` if it is not set.
+pub fn git_config(key: &str) -> Option<String> {
+    let output = Command::new("git").args(["config", "--get", key]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the commits of all branches and tags of the repository at `repo` since a date, `(short hash, date,
+/// subject)` oldest first. `author` matches name or email like `git log --author`.
+pub fn git_log_activity(repo: &Path, since: &str, author: Option<&str>) -> Vec<(String, String, String)> {
+    let mut command = Command::new("git");
+    // not `--all`, the "index on <branch>" commits of stashes have a single parent and would count as work
+    command.arg("-C").arg(repo).args([
+        "log",
+        "--branches",
+        "--tags",
+        "--remotes",
+        "--no-merges",
+        "--reverse",
+        "--date=short",
+        "--format=%h%x00%ad%x00%s",
+    ]);
+    command.arg(format!("--since={}", since));
+    if let Some(author) = author {
+        command.arg(format!("--author={}", author));
+    }
+
+    match command.output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\0');
+                Some((
+                    parts.next()?.to_string(),
+                    parts.next()?.to_string(),
+                    parts.next()?.to_string(),
+                ))
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns a config value as seen from the repository at `repo`.
+pub fn git_repo_config(repo: &Path, key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["config", "--get", key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the name of the current branch, empty when HEAD is detached.
+pub fn git_current_branch() -> String {
+    match Command::new("git").args(["branch", "--show-current"]).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        _ => "".to_string(),
+    }
+}
+
+/// Returns the messages of the last `count` non-merge commits, optionally limited to commits touching `paths`.
+pub fn git_log_messages(count: usize, paths: &[String]) -> Vec<String> {
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--format=%B%x00"])
+        .arg(format!("--max-count={}", count))
+        .arg("--")
+        .args(paths)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .map(|message| message.trim())
+            .filter(|message| !message.is_empty())
+            .map(|message| message.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the `<short hash> <subject>` lines of the non-merge commits in `from..to`, oldest first.
+pub fn git_log_oneline(from: &str, to: &str) -> Vec<String> {
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--reverse", "--format=%h %s"])
+        .arg(format!("{}..{}", from, to))
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the best common ancestor of two commits.
+pub fn git_merge_base(a: &str, b: &str) -> Option<String> {
+    let output = Command::new("git").args(["merge-base", a, b]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Turns `a..b`, `a...b` (from the merge base) or a single ref (up to HEAD) into the two commits to diff
+pub fn git_resolve_range(range: &str) -> anyhow::Result<(String, String)> {
+    let or_head = |s: &str| {
+        if s.is_empty() {
+            "HEAD".to_string()
+        } else {
+            s.to_string()
+        }
+    };
+
+    if let Some((from, to)) = range.split_once("...") {
+        let (from, to) = (or_head(from), or_head(to));
+        let base = git_merge_base(&from, &to)
+            .ok_or_else(|| anyhow::anyhow!("No common ancestor of `{}` and `{}`", from, to))?;
+        return Ok((base, to));
+    }
+    match range.split_once("..") {
+        Some((from, to)) => Ok((or_head(from), or_head(to))),
+        None => Ok((range.to_string(), "HEAD".to_string())),
+    }
+}
+
+/// Returns the short hash and full message of the non-merge commits in `from..to`, oldest first.
+/// Without `from` all commits reachable from `to` are returned.
+pub fn git_log_commits(from: Option<&str>, to: &str) -> Vec<(String, String)> {
+    let range = match from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    };
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--reverse", "--format=%h%x00%B%x1e"])
+        .arg(range)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split('\x1e')
+            .filter_map(|record| record.trim().split_once('\0'))
+            .map(|(hash, message)| (hash.to_string(), message.trim().to_string()))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the committer date of a commit as `YYYY-MM-DD`.
+pub fn git_commit_date(rev: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%cs", rev])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the full hash of a revision, `None` if it does not exist.
+pub fn git_rev_parse(rev: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns whether `ancestor` is reachable from `rev`.
+pub fn git_is_ancestor(ancestor: &str, rev: &str) -> bool {
+    Command::new("git")
+        .args(["merge-base", "--is-ancestor", ancestor, rev])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Returns whether `from..to` contains merge commits.
+pub fn git_has_merges(from: &str, to: &str) -> bool {
+    match Command::new("git")
+        .args(["rev-list", "--merges", "--max-count=1"])
+        .arg(format!("{}..{}", from, to))
+        .output()
+    {
+        Ok(output) if output.status.success() => !output.stdout.is_empty(),
+        _ => false,
+    }
+}
+
+/// Returns the full message of a commit.
+pub fn git_commit_message(rev: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%B", rev])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the remote-tracking branches that contain a commit, i.e. where it has been pushed to.
+pub fn git_remote_branches_containing(rev: &str) -> Vec<String> {
+    match Command::new("git")
+        .args(["branch", "--remotes", "--format=%(refname:short)", "--contains", rev])
+        .output()
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the commits that last changed the given lines of a file as of `rev`, full hashes in line order.
+pub fn git_blame_commits(rev: &str, path: &str, lines: &[usize]) -> Vec<String> {
+    let mut command = Command::new("git");
+    command.args(["blame", "--porcelain"]);
+    for line in lines {
+        command.arg(format!("-L{},{}", line, line));
+    }
+    let output = command.args([rev, "--", path]).output();
+
+    match output {
+        // each blamed line starts with "<hash> <original line> <final line>"
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .filter(|(hash, _)| hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|(hash, _)| hash.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns whether `name` is a valid branch name.
+pub fn git_check_branch_name(name: &str) -> bool {
+    Command::new("git")
+        .args(["check-ref-format", "--branch", name])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Returns the upstream branch of the current branch, e.g. `origin/main`.
+pub fn git_upstream() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{upstream}"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the tags reachable from HEAD.
+pub fn git_merged_tags() -> Vec<String> {
+    match Command::new("git").args(["tag", "--merged", "HEAD"]).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the default branch of the `origin` remote, e.g. `origin/main`, from `refs/remotes/origin/HEAD`.
+pub fn git_default_branch() -> Option<String> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The `:(exclude)` pathspecs of `ignore_filenames`
+fn exclude_pathspecs() -> Vec<String> {
+    ignore_filenames()
+        .iter()
+        .map(|path| format!(":(exclude){}", path))
+        .collect()
+}
+
 fn ignore_filenames() -> Vec<&'static str> {
     vec![
         /* Rust files */
@@ -86,6 +725,226 @@ pub fn git_commit(message: &str, dry_run: bool) -> anyhow::Result<()> {
     }
 }
 
+/// Replaces the message of HEAD, also committing the staged changes.
+pub fn git_commit_amend(message: &str, dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    let output = Command::new("git")
+        .args(["commit", "--amend", "-m", message])
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("amend failed"))
+    }
+}
+
+/// Moves the current branch to `rev`, keeping the changes of the commits in between staged.
+pub fn git_reset_soft(rev: &str, dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    let output = Command::new("git").args(["reset", "--soft", rev]).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "reset failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Resets the index entries of the paths to HEAD, the working tree is left alone.
+pub fn git_unstage(paths: &[String]) -> anyhow::Result<()> {
+    let output = Command::new("git").args(["reset", "-q", "--"]).args(paths).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "reset failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Whether the file is tracked by git, ignored and untracked files are not.
+pub fn git_is_tracked(path: &str) -> bool {
+    Command::new("git")
+        .args(["ls-files", "--error-unmatch", "--", path])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Creates a branch at HEAD and switches to it, uncommitted changes are carried over.
+pub fn git_switch_create(name: &str) -> anyhow::Result<()> {
+    let output = Command::new("git").args(["switch", "-c", name]).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "switch failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Stashes the local changes under the pathspecs with a message.
+pub fn git_stash_push(message: &str, include_untracked: bool, paths: &[String], dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    let mut command = Command::new("git");
+    command.args(["stash", "push", "-m", message]);
+    if include_untracked {
+        command.arg("--include-untracked");
+    }
+    let output = command.arg("--").args(paths).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "stash failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Stages the given files.
+pub fn git_add(paths: &[String], dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    let output = Command::new("git").arg("add").arg("--").args(paths).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("add failed"))
+    }
+}
+
+/// Creates an annotated tag on HEAD.
+pub fn git_tag(name: &str, message: &str, dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    // the message is Markdown, keep its `#` headings
+    let output = Command::new("git")
+        .args(["tag", "-a", "--cleanup=verbatim", name, "-m", message])
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "tag failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Environment variable telling `gitbuddy sequence-editor` where the prepared todo list is
+pub const REBASE_TODO_ENV: &str = "GITBUDDY_REBASE_TODO";
+
+/// Runs `git rebase -i` onto `base` with a prepared todo list instead of asking the user to edit one.
+/// Local changes are stashed for the rebase and restored afterwards.
+pub fn git_rebase_with_todo(base: &str, todo: &Path) -> anyhow::Result<()> {
+    // git runs the sequence editor through its shell, gitbuddy itself copies the todo list
+    let exe = std::env::current_exe()?.to_string_lossy().replace('\'', r"'\''");
+    let output = Command::new("git")
+        .args(["rebase", "-i", "--autostash", base])
+        .env("GIT_SEQUENCE_EDITOR", format!("'{}' sequence-editor", exe))
+        .env(REBASE_TODO_ENV, todo)
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "rebase failed, run `git rebase --abort` to restore the branch: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Writes the index as a tree object and returns its id, a snapshot to restore the index from.
+pub fn git_write_tree() -> Option<String> {
+    let output = Command::new("git").args(["write-tree"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Replaces the index with a tree, the working tree is left alone.
+pub fn git_read_tree(tree: &str) -> anyhow::Result<()> {
+    let output = Command::new("git").args(["read-tree", tree]).output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "read-tree failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Applies a patch to the index only.
+pub fn git_apply_cached(patch: &str) -> anyhow::Result<()> {
+    let mut child = Command::new("git")
+        .args(["apply", "--cached", "--whitespace=nowarn", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("apply failed"))?
+        .write_all(patch.as_bytes())?;
+    let output = child.wait_with_output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "apply failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Runs `git rebase -i --autosquash` onto `base`, accepting the todo list as git prepares it.
+pub fn git_rebase_autosquash(base: &str) -> anyhow::Result<()> {
+    let output = Command::new("git")
+        .args(["rebase", "-i", "--autosquash", "--autostash", base])
+        .env("GIT_SEQUENCE_EDITOR", "true")
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "rebase failed, run `git rebase --abort` to restore the branch: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
 /// Pushes the changes to the remote repository.
 pub fn git_push(dry_run: bool) -> anyhow::Result<()> {
     if dry_run {
@@ -115,6 +974,81 @@ mod test {
         assert!(!filenames.iter().any(|s| s.is_empty()));
     }
 
+    #[test]
+    fn test_parse_changes() {
+        let name_status = "M\tsrc/main.rs\nR087\tsrc/old.rs\tsrc/new.rs\nA\tlogo.png\nM\trun.sh\nD\tREADME.md\n";
+        let numstat = "10\t2\tsrc/main.rs\n\
+                       3\t1\tsrc/{old.rs => new.rs}\n\
+                       -\t-\tlogo.png\n\
+                       0\t0\trun.sh\n\
+                       0\t40\tREADME.md\n \
+                       rename src/{old.rs => new.rs} (87%)\n \
+                       create mode 100644 logo.png\n \
+                       mode change 100644 => 100755 run.sh\n \
+                       delete mode 100644 README.md\n";
+
+        let changes = parse_changes(name_status, numstat);
+
+        assert_eq!(changes.len(), 5);
+        assert_eq!(changes[0].kind, ChangeKind::Modified);
+        assert_eq!((changes[0].added, changes[0].deleted), (Some(10), Some(2)));
+        assert_eq!(
+            changes[1].kind,
+            ChangeKind::Renamed {
+                from: "src/old.rs".to_string(),
+                similarity: 87
+            }
+        );
+        assert_eq!(changes[1].path, "src/new.rs");
+        assert_eq!(changes[2].kind, ChangeKind::Added);
+        assert_eq!((changes[2].added, changes[2].deleted), (None, None));
+        assert_eq!(
+            changes[3].kind,
+            ChangeKind::ModeChanged {
+                old_mode: "100644".to_string(),
+                new_mode: "100755".to_string()
+            }
+        );
+        assert_eq!(changes[4].kind, ChangeKind::Deleted);
+
+        let summary = change_summary(&changes);
+        println!("{}", summary);
+        assert!(summary.starts_with("Changed files (5 files, +13 -43):"));
+        assert!(summary.contains("renamed   src/old.rs -> src/new.rs (87% similar, +3 -1)"));
+        assert!(summary.contains("added     logo.png (binary)"));
+    }
+
+    #[test]
+    fn test_git_log_activity_skips_stashes() {
+        let repo = std::env::temp_dir().join(format!("gitbuddy-activity-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q"]);
+        std::fs::write(repo.join("a.txt"), "one").unwrap();
+        git(&["add", "a.txt"]);
+        git(&["commit", "-q", "-m", "feat: one"]);
+        std::fs::write(repo.join("a.txt"), "two").unwrap();
+        git(&["stash", "-q"]);
+
+        let subjects: Vec<String> = git_log_activity(&repo, "1 year ago", None)
+            .into_iter()
+            .map(|(_, _, subject)| subject)
+            .collect();
+        let _ = std::fs::remove_dir_all(&repo);
+        assert_eq!(subjects, vec!["feat: one"]);
+    }
+
     #[test]
     fn test_git_stage_diff() {
         let diff = git_stage_diff();
@@ -122,4 +1056,10 @@ mod test {
         println!("diff: {:?}", diff);
         assert!(!diff.is_empty());
     }
+
+    #[test]
+    fn test_git_filtered_diff_unknown_revision() {
+        assert_eq!(git_commit_diff("no-such-revision"), "");
+        assert_eq!(git_range_diff("no-such-revision", "HEAD"), "");
+    }
 }
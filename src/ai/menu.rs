@@ -0,0 +1,256 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::commit::{CommitMessage, Conventional};
+
+/// What to do with the generated commit message
+#[derive(Debug, PartialEq, Eq)]
+pub enum Choice {
+    Accept,
+    Edit,
+    /// generate again, optionally with instructions for the model
+    Regenerate(Option<String>),
+    Type(String),
+    /// replace the scope, `None` removes it
+    Scope(Option<String>),
+    Copy,
+    Abort,
+}
+
+/// Which of several generated candidates to continue with
+#[derive(Debug, PartialEq, Eq)]
+pub enum Pick {
+    One(usize),
+    /// ask the model to combine these candidates into one message
+    Merge(Vec<usize>),
+    Abort,
+}
+
+/// Shows the numbered candidates with their type and scope and asks the user to pick or merge
+pub fn pick(candidates: &[String]) -> Pick {
+    for (i, candidate) in candidates.iter().enumerate() {
+        let message = CommitMessage::parse(candidate);
+        let label = match Conventional::parse(&message.header) {
+            Some(c) => match c.scope {
+                Some(scope) => format!("{}({})", c.kind, scope),
+                None => c.kind,
+            },
+            None => "-".to_string(),
+        };
+        println!("{} {}", format!("[{}]", i + 1).bold(), label.yellow());
+        for line in candidate.lines() {
+            println!("    {}", line.cyan());
+        }
+    }
+
+    loop {
+        let question = format!(
+            "Pick {}, merge with {} or {}uit (1) ",
+            format!("[1-{}]", candidates.len()).bold(),
+            "[m 1 2 ..]".bold(),
+            "[q]".bold()
+        );
+        let input = match ask(&question) {
+            Some(input) => input,
+            None => return Pick::Abort,
+        };
+
+        match parse_pick(&input, candidates.len()) {
+            Some(pick) => return pick,
+            None => println!("Unknown option `{}`", input),
+        }
+    }
+}
+
+fn parse_pick(input: &str, count: usize) -> Option<Pick> {
+    let index = |s: &str| {
+        s.parse::<usize>()
+            .ok()
+            .filter(|i| (1..=count).contains(i))
+            .map(|i| i - 1)
+    };
+
+    let input = input.trim().to_lowercase();
+    match input.as_str() {
+        "" => Some(Pick::One(0)),
+        "q" => Some(Pick::Abort),
+        _ => match input.strip_prefix('m') {
+            Some(rest) => {
+                let indices: Option<Vec<usize>> = rest
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|s| !s.is_empty())
+                    .map(index)
+                    .collect();
+                // merging needs at least two candidates, all of them when none are given
+                match indices {
+                    Some(indices) if indices.is_empty() => Some(Pick::Merge((0..count).collect())),
+                    Some(indices) if indices.len() >= 2 => Some(Pick::Merge(indices)),
+                    _ => None,
+                }
+            }
+            None => index(&input).map(Pick::One),
+        },
+    }
+}
+
+/// Shows the message and asks the user what to do with it
+pub fn choose(message: &str) -> Choice {
+    println!("--------------------------------------");
+    println!("{}", message.cyan().bold());
+    println!("--------------------------------------");
+
+    loop {
+        let input = ask(&format!(
+            "{}ccept  {}dit  {}egenerate  {}nstruct  {}ype  {}cope  {}opy  {}uit (A) ",
+            "[a]".bold(),
+            "[e]".bold(),
+            "[r]".bold(),
+            "[i]".bold(),
+            "[t]".bold(),
+            "[s]".bold(),
+            "[c]".bold(),
+            "[q]".bold(),
+        ));
+
+        let input = match input {
+            Some(input) => input,
+            // stdin closed, nobody is there to answer
+            None => return Choice::Abort,
+        };
+
+        match input.to_lowercase().as_str() {
+            "" | "a" | "y" => return Choice::Accept,
+            "e" => return Choice::Edit,
+            "r" => return Choice::Regenerate(None),
+            "i" => {
+                if let Some(instructions) = ask("Instructions (e.g. \"shorter\", \"mention the migration\"): ") {
+                    if !instructions.is_empty() {
+                        return Choice::Regenerate(Some(instructions));
+                    }
+                }
+            }
+            "t" => {
+                if let Some(kind) = ask("Type: ") {
+                    if !kind.is_empty() {
+                        return Choice::Type(kind);
+                    }
+                }
+            }
+            "s" => {
+                if let Some(scope) = ask("Scope (empty to remove): ") {
+                    return Choice::Scope(if scope.is_empty() { None } else { Some(scope) });
+                }
+            }
+            "c" => return Choice::Copy,
+            "q" | "n" => return Choice::Abort,
+            _ => println!("Unknown option `{}`", input),
+        }
+    }
+}
+
+/// Prints the question and reads one trimmed line, `None` once stdin is closed
+pub fn ask(question: &str) -> Option<String> {
+    print!("{}", question);
+    std::io::stdout().flush().ok()?;
+
+    let mut input = String::new();
+    match std::io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+/// Replaces the Conventional Commits type of the header
+pub fn set_type(message: &str, kind: &str) -> Result<String> {
+    replace_header(message, |conventional| conventional.kind = kind.to_string())
+}
+
+/// Replaces or removes the Conventional Commits scope of the header
+pub fn set_scope(message: &str, scope: Option<&str>) -> Result<String> {
+    replace_header(message, |conventional| {
+        conventional.scope = scope.map(|s| s.to_string())
+    })
+}
+
+fn replace_header(message: &str, change: impl FnOnce(&mut Conventional)) -> Result<String> {
+    let parsed = CommitMessage::parse(message);
+    let mut conventional = Conventional::parse(&parsed.header)
+        .ok_or_else(|| anyhow!("`{}` is not a Conventional Commits header", parsed.header))?;
+    change(&mut conventional);
+
+    Ok(match message.trim().split_once('\n') {
+        Some((_, rest)) => format!("{}\n{}", conventional.header(), rest),
+        None => conventional.header(),
+    })
+}
+
+/// Copies the text with the clipboard tool of the platform
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let candidates: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if cfg!(windows) {
+        &[("clip", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+
+    for (program, args) in candidates {
+        let child = Command::new(program).args(*args).stdin(Stdio::piped()).spawn();
+        if let Ok(mut child) = child {
+            if let Some(stdin) = child.stdin.as_mut() {
+                stdin.write_all(text.as_bytes())?;
+            }
+            if child.wait()?.success() {
+                return Ok(());
+            }
+        }
+    }
+
+    let programs: Vec<&str> = candidates.iter().map(|(program, _)| *program).collect();
+    Err(anyhow!(
+        "No clipboard tool found, install one of: {}",
+        programs.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_pick() {
+        assert_eq!(parse_pick("", 3), Some(Pick::One(0)));
+        assert_eq!(parse_pick("2", 3), Some(Pick::One(1)));
+        assert_eq!(parse_pick("4", 3), None);
+        assert_eq!(parse_pick("m 1,3", 3), Some(Pick::Merge(vec![0, 2])));
+        assert_eq!(parse_pick("m", 3), Some(Pick::Merge(vec![0, 1, 2])));
+        assert_eq!(parse_pick("m 2", 3), None);
+        assert_eq!(parse_pick("q", 3), Some(Pick::Abort));
+    }
+
+    #[test]
+    fn test_change_header() {
+        let message = "feat(api): add login\n\nUses the new session store.";
+
+        assert_eq!(
+            set_type(message, "fix").unwrap(),
+            "fix(api): add login\n\nUses the new session store."
+        );
+        assert_eq!(
+            set_scope(message, Some("web")).unwrap().lines().next(),
+            Some("feat(web): add login")
+        );
+        assert_eq!(
+            set_scope(message, None).unwrap().lines().next(),
+            Some("feat: add login")
+        );
+        assert!(set_type("Add login", "fix").is_err());
+    }
+}
@@ -1,15 +1,56 @@
+use std::collections::HashSet;
 use std::time::Instant;
 
+use anyhow::{anyhow, Result};
 use colored::Colorize;
 
-use crate::ai::git::{git_stage_diff, git_stage_filenames};
+use crate::ai::git::{
+    change_summary, git_current_branch, git_log_messages, git_stage_changes, git_stage_diff, git_stage_filenames,
+    git_toplevel,
+};
+use crate::ai::menu::{Choice, Pick};
+use crate::commit::commitlint;
+use crate::commit::language;
+use crate::commit::rules::{Rules, Violation};
+use crate::config;
+use crate::config::repo::RepoConfig;
+use crate::lint;
 use crate::llm;
-use crate::llm::PromptModel;
-use crate::prompt::Prompt;
+use crate::llm::{ChatMessage, PromptModel};
+use crate::prompt::{template, PromptContext};
+use crate::scope::infer_scopes;
+use crate::style::preset::Preset;
 
-mod git;
+pub(crate) mod editor;
+pub(crate) mod git;
+pub(crate) mod menu;
 
-pub fn handler(push: bool, dry_run: bool, vendor: Option<PromptModel>, model: Option<String>, prompt: Prompt) {
+/// Options of a single commit message generation
+#[derive(Default)]
+pub struct Options {
+    /// push the commit to the remote repository
+    pub push: bool,
+    /// generate commit message but not commit
+    pub dry_run: bool,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+    /// name of the prompt template, defaults to the prompt of the style or `p1`
+    pub prompt: Option<String>,
+    /// commit message style, defaults to the style of the repository config
+    pub style: Option<Preset>,
+    /// language of the commit message, defaults to the repository and then the global config
+    pub language: Option<String>,
+    /// number of recent commit messages used as style examples
+    pub history: usize,
+    /// only sample history of commits touching the staged files
+    pub history_paths: bool,
+    /// number of candidate messages to generate and pick from
+    pub candidates: usize,
+    /// open the generated message in the git editor instead of the menu
+    pub edit: bool,
+}
+
+pub fn handler(options: Options) {
     if !is_git_directory() {
         println!("Not git directory");
         return;
@@ -20,34 +61,58 @@ pub fn handler(push: bool, dry_run: bool, vendor: Option<PromptModel>, model: Op
         return;
     }
 
-    let filenames = git_stage_filenames();
-    if filenames.is_empty() {
-        println!("No files added to staging! Did you forget to run `git add` ?");
-        return;
-    }
-
-    let diff_content = git_stage_diff();
-    // let diff_content = format!("Code changes: \n```\n{}\n```", git_stage_diff());
-
-    println!("Generating commit message by LLM...");
-
-    let start = Instant::now();
-    let llm_result = llm::llm_request(&diff_content, vendor, model, prompt).unwrap();
-    let duration = start.elapsed();
-
-    let usage_message = format!(
-        "duration={:?} - Usage={}(completion={}, prompt={})]",
-        duration, llm_result.total_tokens, llm_result.completion_tokens, llm_result.prompt_tokens
-    );
+    let prepared = match prepare_staged(&options) {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
 
-    println!("{}  {}", "Completed!".green(), usage_message.truecolor(128, 128, 128));
+    let mut conversation = prepared.conversation();
+    let commit_message = match generate(&options, &prepared, &conversation, options.candidates)
+        .and_then(|candidates| select(&options, &prepared, &conversation, candidates))
+    {
+        Ok(Some(message)) => message,
+        Ok(None) => {
+            println!("{}", "Cancel commit".red());
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
 
-    if !llm::confirm_commit(llm_result.commit_message.as_str()) {
-        println!("{}", "Cancel commit".red());
-        return;
-    }
+    let commit_message = if options.edit {
+        match editor::edit(&commit_message, &prepared.context.files) {
+            Ok(edited) if edited.is_empty() => {
+                println!("{}", "Aborting commit due to empty commit message".red());
+                return;
+            }
+            Ok(edited) => {
+                // the editor skips the menu, so warn here as the menu does
+                for violation in prepared.validate(&edited) {
+                    println!("{} {}", "warning:".yellow().bold(), violation);
+                }
+                edited
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        }
+    } else {
+        match review(&options, &prepared, &mut conversation, commit_message) {
+            Some(message) => message,
+            None => {
+                println!("{}", "Cancel commit".red());
+                return;
+            }
+        }
+    };
 
-    let result = git::git_commit(llm_result.commit_message.trim(), dry_run);
+    let result = git::git_commit(commit_message.trim(), options.dry_run);
     match result {
         Ok(_) => {
             println!("{}", "Commit success!!!".green().bold());
@@ -58,8 +123,8 @@ pub fn handler(push: bool, dry_run: bool, vendor: Option<PromptModel>, model: Op
     }
 
     // push
-    if push {
-        match git::git_push(dry_run) {
+    if options.push {
+        match git::git_push(options.dry_run) {
             Ok(_) => {
                 println!("{}", "Push success!!!".green())
             }
@@ -70,10 +135,268 @@ pub fn handler(push: bool, dry_run: bool, vendor: Option<PromptModel>, model: Op
     }
 }
 
+const DEFAULT_PROMPT: &str = "p1";
+
+/// The rendered prompt together with what is needed to check the generated message
+pub(crate) struct PreparedPrompt {
+    pub context: PromptContext,
+    pub style: Option<Preset>,
+    pub system_prompt: String,
+    pub user_message: String,
+}
+
+impl PreparedPrompt {
+    /// The system prompt and the diff as the start of a conversation
+    pub fn conversation(&self) -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::system(&self.system_prompt),
+            ChatMessage::user(&self.user_message),
+        ]
+    }
+
+    /// Applies the safe fixes and the style formatting to a generated message
+    pub fn format(&self, message: &str) -> String {
+        lint::fix(message, &self.context.rules, self.style)
+    }
+
+    /// The rules the message breaks
+    pub fn validate(&self, message: &str) -> Vec<Violation> {
+        lint::validate(message, &self.context.rules, self.style)
+    }
+}
+
+/// Shows the menu until the message is accepted, `None` when the user quits
+fn review(
+    options: &Options,
+    prepared: &PreparedPrompt,
+    conversation: &mut Vec<ChatMessage>,
+    mut commit_message: String,
+) -> Option<String> {
+    loop {
+        for violation in prepared.validate(&commit_message) {
+            println!("{} {}", "warning:".yellow().bold(), violation);
+        }
+
+        match menu::choose(&commit_message) {
+            Choice::Accept => return Some(commit_message),
+            Choice::Abort => return None,
+            Choice::Edit => match editor::edit(&commit_message, &prepared.context.files) {
+                Ok(edited) if edited.is_empty() => println!("Empty message, keeping the previous one"),
+                Ok(edited) => commit_message = edited,
+                Err(e) => eprintln!("{}", e),
+            },
+            Choice::Regenerate(instructions) => {
+                // keep the conversation so the model sees what it answered and how to improve it
+                conversation.push(ChatMessage::assistant(&commit_message));
+                conversation.push(ChatMessage::user(&match instructions {
+                    Some(instructions) => format!(
+                        "Revise the commit message according to these instructions: {}\n\
+                         Output ONLY the commit message.",
+                        instructions
+                    ),
+                    None => "Write a different commit message for the same changes. Output ONLY the commit message."
+                        .to_string(),
+                }));
+                match generate(options, prepared, conversation, options.candidates)
+                    .and_then(|candidates| select(options, prepared, conversation, candidates))
+                {
+                    Ok(Some(message)) => commit_message = message,
+                    Ok(None) => {}
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            Choice::Type(kind) => match menu::set_type(&commit_message, &kind) {
+                Ok(message) => commit_message = message,
+                Err(e) => eprintln!("{}", e),
+            },
+            Choice::Scope(scope) => match menu::set_scope(&commit_message, scope.as_deref()) {
+                Ok(message) => commit_message = message,
+                Err(e) => eprintln!("{}", e),
+            },
+            Choice::Copy => match menu::copy_to_clipboard(&commit_message) {
+                Ok(_) => println!("{}", "Copied to clipboard".green()),
+                Err(e) => eprintln!("{}", e),
+            },
+        }
+    }
+}
+
+/// Requests `n` candidate commit messages for the conversation and formats them into the style
+fn generate(
+    options: &Options,
+    prepared: &PreparedPrompt,
+    conversation: &[ChatMessage],
+    n: usize,
+) -> Result<Vec<String>> {
+    println!("Generating commit message by LLM...");
+
+    let start = Instant::now();
+    let llm_result = llm::llm_request_choices(conversation, options.vendor, options.model.clone(), n)?;
+    let duration = start.elapsed();
+
+    println!(
+        "{}  {}",
+        "Completed!".green(),
+        llm_result.usage(duration).truecolor(128, 128, 128)
+    );
+
+    let mut candidates: Vec<String> = llm_result.choices.iter().map(|c| prepared.format(c)).collect();
+    // separate requests with a low temperature tend to answer the same
+    let mut seen = HashSet::new();
+    candidates.retain(|candidate| !candidate.trim().is_empty() && seen.insert(candidate.clone()));
+    if candidates.is_empty() {
+        return Err(anyhow!("No commit message returned"));
+    }
+    Ok(candidates)
+}
+
+/// Lets the user pick one of the candidates or merge several, `None` when the user quits
+fn select(
+    options: &Options,
+    prepared: &PreparedPrompt,
+    conversation: &[ChatMessage],
+    mut candidates: Vec<String>,
+) -> Result<Option<String>> {
+    if candidates.len() <= 1 {
+        return Ok(candidates.pop());
+    }
+
+    match menu::pick(&candidates) {
+        Pick::One(i) => Ok(Some(candidates.swap_remove(i))),
+        Pick::Merge(indices) => {
+            let listed: Vec<String> = indices
+                .iter()
+                .enumerate()
+                .map(|(n, i)| format!("Message {}:\n{}", n + 1, candidates[*i]))
+                .collect();
+            let mut conversation = conversation.to_vec();
+            conversation.push(ChatMessage::user(&format!(
+                "Merge the following commit messages into a single one that keeps the best of each. \
+                 Output ONLY the commit message.\n\n{}",
+                listed.join("\n\n")
+            )));
+            Ok(generate(options, prepared, &conversation, 1)?.pop())
+        }
+        Pick::Abort => Ok(None),
+    }
+}
+
+/// Renders the prompt for the staged changes
+pub(crate) fn prepare_staged(options: &Options) -> Result<PreparedPrompt> {
+    let filenames = git_stage_filenames();
+    if filenames.is_empty() {
+        return Err(anyhow!("No files added to staging! Did you forget to run `git add` ?"));
+    }
+
+    let summary = change_summary(&git_stage_changes());
+    let diff_content = format!("{}\n{}", summary, git_stage_diff());
+
+    prepare_prompt(options, filenames, diff_content)
+}
+
+/// Generates a single commit message for the staged changes without asking the user
+pub(crate) fn generate_message(options: &Options) -> Result<String> {
+    let prepared = prepare_staged(options)?;
+    generate_once(options, &prepared)
+}
+
+/// Generates a single commit message for changes other than the staged ones, e.g. those of an existing commit
+pub(crate) fn generate_message_for(options: &Options, filenames: Vec<String>, diff: String) -> Result<String> {
+    let prepared = prepare_prompt(options, filenames, diff)?;
+    generate_once(options, &prepared)
+}
+
+fn generate_once(options: &Options, prepared: &PreparedPrompt) -> Result<String> {
+    generate(options, prepared, &prepared.conversation(), 1)?
+        .pop()
+        .ok_or_else(|| anyhow!("No commit message returned"))
+}
+
+/// Collects the repository context for the staged changes and renders the selected prompt template
+fn prepare_prompt(options: &Options, filenames: Vec<String>, diff: String) -> Result<PreparedPrompt> {
+    let repo_config = RepoConfig::load()?;
+    let style = options.style.or(repo_config.style);
+
+    let template = match (&options.prompt, style) {
+        (Some(name), _) => template::find(name)?.content,
+        (None, Some(style)) => style.prompt(),
+        (None, None) => template::find(DEFAULT_PROMPT)?.content,
+    };
+
+    let rules = configured_rules(options, &repo_config, style)?;
+    let mut context = PromptContext {
+        diff,
+        branch: git_current_branch(),
+        language: language::normalize(rules.language.as_deref().unwrap_or("English")),
+        rules,
+        ..Default::default()
+    };
+
+    if options.history > 0 {
+        let paths = if options.history_paths {
+            filenames.clone()
+        } else {
+            vec![]
+        };
+        context.examples = git_log_messages(options.history, &paths);
+    }
+
+    apply_inferred_scopes(&mut context.rules, &filenames, &repo_config, style);
+
+    context.files = filenames;
+    let (system_prompt, user_message) = context.messages(&template)?;
+    Ok(PreparedPrompt {
+        context,
+        style,
+        system_prompt,
+        user_message,
+    })
+}
+
+/// The message rules configured for the repository: those of the style, replaced by a commitlint
+/// config when there is one, and the language
+pub(crate) fn configured_rules(options: &Options, repo_config: &RepoConfig, style: Option<Preset>) -> Result<Rules> {
+    let mut rules = style.map(|style| style.rules()).unwrap_or_default();
+    if let Some(commitlint_rules) = commitlint::load(&git_toplevel().unwrap_or_default())? {
+        rules = commitlint_rules;
+    }
+
+    rules.language = configured_language(options.language.clone(), repo_config);
+    Ok(rules)
+}
+
+/// Restricts the scopes to those inferred from the staged paths, they are more specific than the ones
+/// allowed by commitlint
+pub(crate) fn apply_inferred_scopes(
+    rules: &mut Rules,
+    filenames: &[String],
+    repo_config: &RepoConfig,
+    style: Option<Preset>,
+) {
+    if style.is_some_and(|style| !style.uses_scopes()) {
+        return;
+    }
+
+    let root = git_toplevel().unwrap_or_default();
+    let mut scopes = infer_scopes(filenames, &repo_config.scope, &root);
+    if scopes.len() == 1 {
+        rules.required_scope = scopes.pop();
+    } else if !scopes.is_empty() {
+        rules.scopes = scopes;
+    }
+}
+
+/// The language given on the command line, else the one of the repository and then the global config
+pub(crate) fn configured_language(language: Option<String>, repo_config: &RepoConfig) -> Option<String> {
+    language
+        .or(repo_config.language.clone())
+        .or_else(|| config::get_config().ok().and_then(|cfg| cfg.default.language))
+}
+
 fn is_git_directory() -> bool {
-    return std::process::Command::new("git").arg("rev-parse").output().is_ok();
+    std::process::Command::new("git").arg("rev-parse").output().is_ok()
 }
 
 fn is_git_installed() -> bool {
-    return std::process::Command::new("git").arg("--version").output().is_ok();
+    std::process::Command::new("git").arg("--version").output().is_ok()
 }
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai;
+use crate::ai::editor;
+use crate::ai::git::{
+    change_summary, git_amend_changes, git_amend_diff, git_commit_amend, git_commit_message,
+    git_remote_branches_containing,
+};
+use crate::ai::menu::ask;
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+/// Generate a new message for HEAD, including the staged changes, and amend it
+pub fn handler(options: ai::Options) -> Result<()> {
+    let old = git_commit_message("HEAD").ok_or_else(|| anyhow!("HEAD does not point to a commit"))?;
+
+    let pushed = git_remote_branches_containing("HEAD");
+    if !pushed.is_empty() {
+        println!(
+            "{} HEAD is already on {}, amending it rewrites published history",
+            "warning:".yellow().bold(),
+            pushed.join(", ")
+        );
+    }
+
+    let changes = git_amend_changes();
+    let filenames: Vec<String> = changes.iter().map(|change| change.path.clone()).collect();
+    let diff = format!(
+        "{}\n{}",
+        change_summary(&changes),
+        truncate_diff(&git_amend_diff(), DEFAULT_DIFF_TOKENS)
+    );
+
+    let mut new = ai::generate_message_for(&options, filenames.clone(), diff.clone())?;
+    loop {
+        for (tag, line) in line_diff(&old, &new) {
+            match tag {
+                '-' => println!("{}", format!("- {}", line).red()),
+                '+' => println!("{}", format!("+ {}", line).green()),
+                _ => println!("  {}", line),
+            }
+        }
+
+        let answer = ask(&format!(
+            "Amend HEAD with the new message? {}es  {}dit  {}egenerate  {}o (Y) ",
+            "[y]".bold(),
+            "[e]".bold(),
+            "[r]".bold(),
+            "[n]".bold()
+        ));
+        match answer.as_deref().map(|a| a.to_lowercase()).as_deref() {
+            Some("" | "y") => break,
+            Some("e") => match editor::edit(&new, &filenames) {
+                Ok(edited) if !edited.is_empty() => new = edited,
+                Ok(_) => println!("Empty message, keeping the generated one"),
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("r") => match ai::generate_message_for(&options, filenames.clone(), diff.clone()) {
+                Ok(message) => new = message,
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("n") | None => {
+                println!("{}", "Cancel amend".red());
+                return Ok(());
+            }
+            Some(other) => println!("Unknown option `{}`", other),
+        }
+    }
+
+    git_commit_amend(new.trim(), options.dry_run)?;
+    println!("{}", "Amend success!!!".green().bold());
+    Ok(())
+}
+
+/// A line based diff of two texts: `-` for removed, `+` for added and ` ` for kept lines
+fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let old: Vec<&str> = old.trim().lines().collect();
+    let new: Vec<&str> = new.trim().lines().collect();
+
+    // longest common subsequence table, commit messages are short enough for the quadratic version
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            diff.push(('+', new[j]));
+            j += 1;
+        } else {
+            diff.push(('-', old[i]));
+            i += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("wip\n\nRefs #12", "feat: add login\n\nRefs #12");
+        assert_eq!(
+            diff,
+            vec![('+', "feat: add login"), ('-', "wip"), (' ', ""), (' ', "Refs #12")]
+        );
+    }
+}
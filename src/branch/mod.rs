@@ -0,0 +1,219 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::ai::git::{
+    change_summary, git_check_branch_name, git_rev_parse, git_switch_create, git_worktree_changes, git_worktree_diff,
+};
+use crate::ai::menu::ask;
+use crate::config::repo::RepoConfig;
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+const BRANCH_PROMPT: &str = r#"You name git branches. From the description of the work or from the diff, answer with
+JSON only, no Markdown fences, in exactly this shape:
+{"type": "feat", "slug": "add-login-endpoint"}
+
+- type is one of: feat, fix, docs, refactor, perf, test, build, ci, chore
+- slug is 2 to 5 lowercase English words joined by hyphens, without ticket numbers"#;
+
+const DEFAULT_PATTERN: &str = "{type}/{slug}";
+const MAX_SLUG_LENGTH: usize = 40;
+
+#[derive(Debug, Deserialize)]
+struct Suggestion {
+    #[serde(rename = "type")]
+    kind: String,
+    slug: String,
+}
+
+/// Options of `gitbuddy branch`
+pub struct Options {
+    /// what the branch is for, the uncommitted changes are used when not given
+    pub description: Option<String>,
+    /// ticket for the `{ticket}` placeholder, found in the description when not given
+    pub ticket: Option<String>,
+    /// only show the name
+    pub dry_run: bool,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+}
+
+/// Suggest a branch name for the described or uncommitted work and switch to it
+pub fn handler(options: Options) -> Result<()> {
+    let repo_config = RepoConfig::load()?;
+    let pattern = repo_config.branch.pattern.as_deref().unwrap_or(DEFAULT_PATTERN);
+    let ticket = options
+        .ticket
+        .clone()
+        .or_else(|| options.description.as_deref().and_then(find_ticket));
+
+    let input = match &options.description {
+        Some(description) => format!("Description: {}", description),
+        None => {
+            let diff = git_worktree_diff(&[]);
+            if !diff.contains("diff --git") {
+                return Err(anyhow!("Nothing to name, describe the work or make some changes first"));
+            }
+            format!(
+                "Diff:\n{}\n{}",
+                change_summary(&git_worktree_changes(&[])),
+                truncate_diff(&diff, DEFAULT_DIFF_TOKENS)
+            )
+        }
+    };
+
+    let mut name = suggest(&options, &input, pattern, ticket.as_deref())?;
+    loop {
+        let problem = if !git_check_branch_name(&name) {
+            Some(format!("`{}` is not a valid branch name", name))
+        } else if git_rev_parse(&format!("refs/heads/{}", name)).is_some() {
+            Some(format!("a branch `{}` already exists", name))
+        } else {
+            None
+        };
+        match &problem {
+            Some(problem) => println!("{} {}", "error:".red().bold(), problem),
+            None if options.dry_run => {
+                println!("{}", name.green().bold());
+                return Ok(());
+            }
+            None => println!("{}", name.green().bold()),
+        }
+
+        let answer = ask(&format!(
+            "Create and switch to this branch? {}es  {}dit  {}egenerate  {}o (Y) ",
+            "[y]".bold(),
+            "[e]".bold(),
+            "[r]".bold(),
+            "[n]".bold()
+        ));
+        match answer.as_deref().map(|a| a.to_lowercase()).as_deref() {
+            Some("" | "y") if problem.is_none() => break,
+            Some("" | "y") => println!("Edit or regenerate the name first"),
+            Some("e") => {
+                if let Some(typed) = ask("Branch name: ").filter(|typed| !typed.is_empty()) {
+                    name = typed;
+                }
+            }
+            Some("r") => match suggest(&options, &input, pattern, ticket.as_deref()) {
+                Ok(suggested) => name = suggested,
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("n") | None => {
+                println!("{}", "Cancel branch".red());
+                return Ok(());
+            }
+            Some(other) => println!("Unknown option `{}`", other),
+        }
+    }
+
+    git_switch_create(&name)?;
+    println!("{} {}", "Switched to a new branch".green(), name);
+    Ok(())
+}
+
+/// Asks the model for the type and slug and renders them into the pattern
+fn suggest(options: &Options, input: &str, pattern: &str, ticket: Option<&str>) -> Result<String> {
+    let result = llm::request_with_progress(
+        "Naming the branch",
+        &[ChatMessage::system(BRANCH_PROMPT), ChatMessage::user(input)],
+        options.vendor,
+        options.model.clone(),
+    )?;
+
+    let answer = result.message();
+    let json = llm::extract_json(answer)?;
+    let suggestion: Suggestion =
+        serde_json::from_str(json).map_err(|e| anyhow!("Can not parse the branch name: {}\n{}", e, answer))?;
+
+    Ok(render(
+        pattern,
+        &slugify(&suggestion.kind),
+        ticket,
+        &slugify(&suggestion.slug),
+    ))
+}
+
+/// Fills the placeholders of the pattern, `{ticket}` is dropped together with its separator when there is none
+fn render(pattern: &str, kind: &str, ticket: Option<&str>, slug: &str) -> String {
+    let mut name = pattern.to_string();
+    match ticket {
+        Some(ticket) => name = name.replace("{ticket}", ticket),
+        None => {
+            for placeholder in [
+                "{ticket}-",
+                "-{ticket}",
+                "{ticket}_",
+                "_{ticket}",
+                "{ticket}/",
+                "{ticket}",
+            ] {
+                name = name.replace(placeholder, "");
+            }
+        }
+    }
+    name.replace("{type}", kind).replace("{slug}", slug)
+}
+
+/// Finds an issue key like `PROJ-123` in a text
+fn find_ticket(text: &str) -> Option<String> {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .find(|word| {
+            word.split_once('-').is_some_and(|(project, number)| {
+                !project.is_empty()
+                    && project.chars().all(|c| c.is_ascii_uppercase())
+                    && !number.is_empty()
+                    && number.chars().all(|c| c.is_ascii_digit())
+            })
+        })
+        .map(|word| word.to_string())
+}
+
+/// Lowercase ASCII words joined by hyphens, cut at a word boundary
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > MAX_SLUG_LENGTH {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    slug
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let slug = slugify("Add the Login endpoint!");
+        assert_eq!(slug, "add-the-login-endpoint");
+
+        assert_eq!(
+            render("{type}/{ticket}-{slug}", "feat", Some("PROJ-123"), &slug),
+            "feat/PROJ-123-add-the-login-endpoint"
+        );
+        assert_eq!(
+            render("{type}/{ticket}-{slug}", "feat", None, &slug),
+            "feat/add-the-login-endpoint"
+        );
+        assert_eq!(render(DEFAULT_PATTERN, "fix", None, "typo"), "fix/typo");
+
+        assert_eq!(
+            find_ticket("fix the crash from PROJ-42, see OPS-1"),
+            Some("PROJ-42".to_string())
+        );
+        assert_eq!(find_ticket("add a login-form"), None);
+        assert!(slugify(&"word ".repeat(20)).len() <= MAX_SLUG_LENGTH);
+    }
+}
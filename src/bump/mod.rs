@@ -0,0 +1,398 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai::git::{
+    git_add, git_commit, git_is_tracked, git_log_commits, git_merged_tags, git_stage_filenames, git_tag, git_toplevel,
+    git_unstage,
+};
+use crate::changelog;
+use crate::changelog::Entry;
+
+/// A `MAJOR.MINOR.PATCH` version, pre-release and build suffixes are not supported
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Parses `1.2.3` or `v1.2.3`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let mut parts = text.split('.');
+        let version = Version {
+            major: parts.next()?.parse().ok()?,
+            minor: parts.next()?.parse().ok()?,
+            patch: parts.next()?.parse().ok()?,
+        };
+        match parts.next() {
+            Some(_) => None,
+            None => Some(version),
+        }
+    }
+
+    pub fn bump(self, level: Level) -> Self {
+        match level {
+            Level::Major => Version {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            Level::Minor => Version {
+                minor: self.minor + 1,
+                patch: 0,
+                ..self
+            },
+            Level::Patch => Version {
+                patch: self.patch + 1,
+                ..self
+            },
+        }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Which part of the version a release increments
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Patch => write!(f, "patch"),
+            Level::Minor => write!(f, "minor"),
+            Level::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// The level a commit asks for: major for breaking changes, minor for `feat`, patch for `fix` and `perf`
+fn level(entry: &Entry) -> Option<Level> {
+    if entry.breaking.is_some() {
+        return Some(Level::Major);
+    }
+    match entry.kind.as_deref() {
+        Some("feat") => Some(Level::Minor),
+        Some("fix") | Some("perf") => Some(Level::Patch),
+        _ => None,
+    }
+}
+
+/// Options of `gitbuddy bump`
+pub struct Options {
+    /// update the version of `Cargo.toml` and `package.json` and commit it before tagging
+    pub write: bool,
+    /// show the next version and the tag message, but change nothing
+    pub dry_run: bool,
+}
+
+/// Compute the next version from the commits since the last version tag and tag it
+pub fn handler(options: Options) -> Result<()> {
+    if options.write && !git_stage_filenames().is_empty() {
+        return Err(anyhow!(
+            "There are staged changes, commit or unstage them first so the release commit only bumps the version"
+        ));
+    }
+
+    let latest = git_merged_tags()
+        .into_iter()
+        .filter_map(|tag| Version::parse(&tag).map(|version| (version, tag)))
+        .max_by(|(a, _), (b, _)| a.cmp(b));
+
+    let commits = git_log_commits(latest.as_ref().map(|(_, tag)| tag.as_str()), "HEAD");
+    let entries: Vec<Entry> = commits
+        .iter()
+        .map(|(hash, message)| Entry::parse(hash, message))
+        .collect();
+    let level = entries
+        .iter()
+        .filter_map(level)
+        .max()
+        .ok_or_else(|| anyhow!("No feat, fix or breaking commits to release since the last version tag"))?;
+
+    // keep the tag naming of the repository, `v1.2.3` unless the previous tags had no prefix
+    let (current, prefix) = match &latest {
+        Some((version, tag)) => (*version, if tag.starts_with('v') { "v" } else { "" }),
+        None => (Version::parse("0.0.0").unwrap(), "v"),
+    };
+    let next = current.bump(level);
+    let tag = format!("{}{}", prefix, next);
+
+    match &latest {
+        Some((_, previous)) => println!(
+            "{} -> {} ({} release, {} commits)",
+            previous,
+            tag.green().bold(),
+            level,
+            entries.len()
+        ),
+        None => println!("{} ({} release, first version tag)", tag.green().bold(), level),
+    }
+
+    let message = tag_message(&tag, &entries);
+    println!("{}", message.truecolor(128, 128, 128));
+
+    if options.write {
+        let written = write_versions(&next, options.dry_run)?;
+        if !written.is_empty() {
+            let files: Vec<String> = written.iter().map(|(path, _)| path.clone()).collect();
+            if !options.dry_run {
+                println!("Updated the version of {}", files.join(", "));
+            }
+            let committed = git_add(&files, options.dry_run)
+                .and_then(|_| git_commit(&format!("chore(release): {}", tag), options.dry_run));
+            if let Err(e) = committed {
+                // do not leave the release half done with rewritten and staged manifests
+                restore_versions(&written)?;
+                return Err(anyhow!("{}, the version files are restored", e));
+            }
+        }
+    }
+
+    git_tag(&tag, &message, options.dry_run)?;
+    if options.dry_run {
+        println!("{}", "Dry run, nothing changed".yellow());
+    } else {
+        println!("{} {}", "Tagged".green(), tag);
+    }
+    Ok(())
+}
+
+fn tag_message(tag: &str, entries: &[Entry]) -> String {
+    let changelog = changelog::render(tag, entries);
+    // the heading of the changelog section becomes the subject line of the tag
+    let body = changelog
+        .split_once('\n')
+        .map(|(_, body)| body.trim())
+        .unwrap_or_default();
+    format!("Release {}\n\n{}", tag, body)
+}
+
+/// Sets the version in the content of a manifest, `None` when it has no version to set
+type VersionUpdater = fn(&str, &Version) -> Option<String>;
+
+/// Writes the version into the `Cargo.toml` and `package.json` of the repository root, returning the changed
+/// files with their previous content. A tracked `Cargo.lock` is updated along with `Cargo.toml`, cargo would
+/// otherwise change it on the next build.
+fn write_versions(version: &Version, dry_run: bool) -> Result<Vec<(String, String)>> {
+    let root = git_toplevel().ok_or_else(|| anyhow!("Not git directory"))?;
+    let updaters: [(&str, VersionUpdater); 2] = [
+        ("Cargo.toml", set_cargo_version),
+        ("package.json", set_package_json_version),
+    ];
+
+    let mut changed = vec![];
+    for (name, update) in updaters {
+        let path = root.join(name);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if let Some(updated) = update(&content, version) {
+            if updated != content {
+                if !dry_run {
+                    fs::write(&path, updated)?;
+                }
+                changed.push((path.to_string_lossy().to_string(), content));
+            }
+        }
+    }
+
+    let lock_path = root.join("Cargo.lock");
+    let package = fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| cargo_package_name(&content));
+    let lock_file = lock_path.to_string_lossy().to_string();
+    if !git_is_tracked(&lock_file) {
+        return Ok(changed);
+    }
+    if let (Some(name), Ok(content)) = (package, fs::read_to_string(&lock_path)) {
+        if let Some(updated) = set_cargo_lock_version(&content, &name, version) {
+            if updated != content {
+                if !dry_run {
+                    fs::write(&lock_path, updated)?;
+                }
+                changed.push((lock_file, content));
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Writes back the content the files had before [`write_versions`] and unstages them
+fn restore_versions(written: &[(String, String)]) -> Result<()> {
+    for (path, content) in written {
+        fs::write(path, content)?;
+    }
+    let files: Vec<String> = written.iter().map(|(path, _)| path.clone()).collect();
+    git_unstage(&files)
+}
+
+/// Replaces the `version` of the `[package]` table, keeping the rest of the file as it is
+fn set_cargo_version(content: &str, version: &Version) -> Option<String> {
+    let mut in_package = false;
+    let mut found = false;
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        }
+
+        let is_version = trimmed
+            .split_once('=')
+            .is_some_and(|(key, value)| key.trim() == "version" && value.trim().starts_with('"'));
+        if in_package && !found && is_version {
+            found = true;
+            lines.push(format!("version = \"{}\"", version));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+
+    found.then(|| join_lines(content, lines))
+}
+
+/// The `name` of the `[package]` table
+fn cargo_package_name(content: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        }
+        if let Some((key, value)) = trimmed.split_once('=') {
+            if in_package && key.trim() == "name" {
+                return Some(value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Replaces the version of the local package in a `Cargo.lock`, the entry of the package without a `source`
+fn set_cargo_lock_version(content: &str, name: &str, version: &Version) -> Option<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut found = false;
+    // the lines of the current `[[package]]` entry, the entry is only known to be local at its end
+    let mut entry: Vec<&str> = vec![];
+    for line in content.lines().chain(std::iter::once("")) {
+        if !line.trim().is_empty() {
+            entry.push(line);
+            continue;
+        }
+
+        let is_local = entry.first() == Some(&"[[package]]")
+            && entry.contains(&format!("name = \"{}\"", name).as_str())
+            && !entry.iter().any(|line| line.starts_with("source = "));
+        for line in entry.drain(..) {
+            if is_local && !found && line.starts_with("version = ") {
+                found = true;
+                lines.push(format!("version = \"{}\"", version));
+            } else {
+                lines.push(line.to_string());
+            }
+        }
+        lines.push(line.to_string());
+    }
+    // the empty line chained to flush the last entry
+    lines.pop();
+
+    found.then(|| join_lines(content, lines))
+}
+
+/// Replaces the top level `"version"` of a `package.json`, keeping the formatting of the file
+fn set_package_json_version(content: &str, version: &Version) -> Option<String> {
+    let mut depth = 0;
+    let mut lines: Vec<String> = vec![];
+    let mut found = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if depth == 1 && !found && trimmed.starts_with("\"version\"") {
+            let indent = &line[..line.len() - trimmed.len()];
+            let comma = if trimmed.trim_end().ends_with(',') { "," } else { "" };
+            lines.push(format!("{}\"version\": \"{}\"{}", indent, version, comma));
+            found = true;
+        } else {
+            lines.push(line.to_string());
+        }
+
+        // strings with braces are rare enough in package.json to count them naively
+        for c in line.chars() {
+            match c {
+                '{' | '[' => depth += 1,
+                '}' | ']' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    found.then(|| join_lines(content, lines))
+}
+
+fn join_lines(original: &str, lines: Vec<String>) -> String {
+    let mut text = lines.join("\n");
+    if original.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_next_version() {
+        let version = Version::parse("v1.4.2").unwrap();
+        assert_eq!(version.bump(Level::Patch).to_string(), "1.4.3");
+        assert_eq!(version.bump(Level::Minor).to_string(), "1.5.0");
+        assert_eq!(version.bump(Level::Major).to_string(), "2.0.0");
+        assert!(Version::parse("1.2.3-rc.1").is_none());
+        assert!(Version::parse("release-1").is_none());
+
+        assert_eq!(level(&Entry::parse("a", "fix: typo")), Some(Level::Patch));
+        assert_eq!(level(&Entry::parse("a", "feat(api): login")), Some(Level::Minor));
+        assert_eq!(level(&Entry::parse("a", "refactor!: drop v1")), Some(Level::Major));
+        assert_eq!(level(&Entry::parse("a", "docs: readme")), None);
+    }
+
+    #[test]
+    fn test_write_versions() {
+        let version = Version::parse("1.3.0").unwrap();
+
+        let cargo = "[package]\nname = \"demo\"\nversion = \"1.2.0\"\n\n[dependencies]\nserde = { version = \"1\" }\n";
+        assert_eq!(
+            set_cargo_version(cargo, &version).unwrap(),
+            "[package]\nname = \"demo\"\nversion = \"1.3.0\"\n\n[dependencies]\nserde = { version = \"1\" }\n"
+        );
+        assert!(set_cargo_version("[workspace]\nmembers = []\n", &version).is_none());
+
+        let lock = "[[package]]\nname = \"demo\"\nversion = \"1.2.0\"\ndependencies = [\n \"serde\",\n]\n\n\
+                    [[package]]\nname = \"demo\"\nversion = \"0.1.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        assert_eq!(cargo_package_name(cargo).as_deref(), Some("demo"));
+        assert_eq!(
+            set_cargo_lock_version(lock, "demo", &version).unwrap(),
+            lock.replacen("version = \"1.2.0\"", "version = \"1.3.0\"", 1)
+        );
+
+        let package = "{\n  \"name\": \"demo\",\n  \"version\": \"1.2.0\",\n  \"dependencies\": {\n    \"version\": \"1\"\n  }\n}\n";
+        assert_eq!(
+            set_package_json_version(package, &version).unwrap(),
+            "{\n  \"name\": \"demo\",\n  \"version\": \"1.3.0\",\n  \"dependencies\": {\n    \"version\": \"1\"\n  }\n}\n"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use anyhow::{anyhow, Result};
+
+use crate::ai;
+use crate::ai::git::{git_commit_date, git_log_commits, git_resolve_range};
+use crate::commit::language;
+use crate::commit::CommitMessage;
+use crate::config::repo::RepoConfig;
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+
+const POLISH_PROMPT: &str = r#"You are a technical writer turning a generated changelog into release notes for users.
+Keep the Markdown structure and the section headings, the commit hashes and the version heading.
+Rewrite the entries so users understand the impact: merge entries describing the same change, drop purely
+internal noise, explain breaking changes and what users have to do. Do not invent changes.
+Write in {language}. Output ONLY the Markdown."#;
+
+/// Keep-a-Changelog sections in the order they are rendered, with the commit types they collect
+const SECTIONS: [(&str, &[&str]); 6] = [
+    ("Added", &["feat"]),
+    ("Changed", &["perf", "refactor", "revert"]),
+    ("Deprecated", &["deprecate"]),
+    ("Removed", &["remove"]),
+    ("Fixed", &["fix"]),
+    ("Security", &["security"]),
+];
+
+/// Types that do not change what users get, left out of the changelog
+const HIDDEN_TYPES: [&str; 6] = ["build", "chore", "ci", "docs", "style", "test"];
+
+/// A commit of the range, parsed as Conventional Commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub hash: String,
+    /// commit type, `None` when the message is not a Conventional Commit
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    pub description: String,
+    /// what breaks, from the `BREAKING CHANGE` footer or the description for a `!` header
+    pub breaking: Option<String>,
+}
+
+impl Entry {
+    pub fn parse(hash: &str, message: &str) -> Self {
+        let parsed = CommitMessage::parse(message);
+        match parsed.conventional() {
+            Some(conventional) => {
+                let note = parsed
+                    .footers
+                    .iter()
+                    .find(|(token, _)| token == "BREAKING CHANGE" || token == "BREAKING-CHANGE")
+                    .map(|(_, value)| value.clone());
+                Entry {
+                    hash: hash.to_string(),
+                    kind: Some(conventional.kind.to_lowercase()),
+                    scope: conventional.scope,
+                    breaking: conventional
+                        .breaking
+                        .then(|| note.unwrap_or(conventional.description.clone())),
+                    description: conventional.description,
+                }
+            }
+            None => Entry {
+                hash: hash.to_string(),
+                kind: None,
+                scope: None,
+                description: parsed.header,
+                breaking: None,
+            },
+        }
+    }
+
+    fn line(&self, text: &str) -> String {
+        match &self.scope {
+            Some(scope) => format!("- **{}:** {} ({})\n", scope, text, self.hash),
+            None => format!("- {} ({})\n", text, self.hash),
+        }
+    }
+}
+
+/// Options of `gitbuddy changelog`
+pub struct Options {
+    /// commits to describe, `<from>..<to>`
+    pub range: String,
+    /// rewrite the changelog into release notes with the LLM
+    pub polish: bool,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Print a Keep-a-Changelog section for the commits of a range
+pub fn handler(options: Options) -> Result<()> {
+    let (from, to) = git_resolve_range(&options.range)?;
+    let commits = git_log_commits(Some(&from), &to);
+    if commits.is_empty() {
+        return Err(anyhow!("No commits in `{}`", options.range));
+    }
+
+    let entries: Vec<Entry> = commits
+        .iter()
+        .map(|(hash, message)| Entry::parse(hash, message))
+        .collect();
+    let title = match (to.as_str(), git_commit_date(&to)) {
+        ("HEAD", _) => "[Unreleased]".to_string(),
+        (to, Some(date)) => format!("[{}] - {}", to, date),
+        (to, None) => format!("[{}]", to),
+    };
+    let changelog = render(&title, &entries);
+    if !options.polish {
+        print!("{}", changelog);
+        return Ok(());
+    }
+
+    let repo_config = RepoConfig::load()?;
+    let language = ai::configured_language(options.language.clone(), &repo_config);
+    let system_prompt = POLISH_PROMPT.replace(
+        "{language}",
+        &language::normalize(language.as_deref().unwrap_or("English")),
+    );
+
+    let result = llm::request_with_progress(
+        "Polishing the changelog",
+        &[ChatMessage::system(&system_prompt), ChatMessage::user(&changelog)],
+        options.vendor,
+        options.model.clone(),
+    )?;
+    println!("{}", result.message());
+    Ok(())
+}
+
+/// Renders the entries as Keep-a-Changelog Markdown, breaking changes first and grouped by scope within a section
+pub fn render(title: &str, entries: &[Entry]) -> String {
+    let mut markdown = format!("## {}\n", title);
+
+    let breaking: Vec<&Entry> = entries.iter().filter(|e| e.breaking.is_some()).collect();
+    if !breaking.is_empty() {
+        markdown.push_str("\n### ⚠ BREAKING CHANGES\n\n");
+        for entry in by_scope(breaking) {
+            markdown.push_str(&entry.line(entry.breaking.as_deref().unwrap_or_default()));
+        }
+    }
+
+    for (section, kinds) in SECTIONS {
+        let section_entries: Vec<&Entry> = entries
+            .iter()
+            .filter(|e| match &e.kind {
+                Some(kind) => kinds.contains(&kind.as_str()),
+                // commits that are not conventional can not be classified, keep them visible
+                None => section == "Changed",
+            })
+            .collect();
+        if section_entries.is_empty() {
+            continue;
+        }
+
+        markdown.push_str(&format!("\n### {}\n\n", section));
+        for entry in by_scope(section_entries) {
+            markdown.push_str(&entry.line(&entry.description));
+        }
+    }
+
+    let other: Vec<&Entry> = entries
+        .iter()
+        .filter(|e| {
+            e.kind.as_ref().is_some_and(|kind| {
+                !HIDDEN_TYPES.contains(&kind.as_str())
+                    && !SECTIONS.iter().any(|(_, kinds)| kinds.contains(&kind.as_str()))
+            })
+        })
+        .collect();
+    if !other.is_empty() {
+        markdown.push_str("\n### Other\n\n");
+        for entry in by_scope(other) {
+            markdown.push_str(&entry.line(&entry.description));
+        }
+    }
+
+    markdown
+}
+
+/// Orders the entries by scope, unscoped first, keeping the commit order within a scope
+fn by_scope(mut entries: Vec<&Entry>) -> Vec<&Entry> {
+    entries.sort_by(|a, b| a.scope.cmp(&b.scope));
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let entries = vec![
+            Entry::parse("a1", "feat(api): add login"),
+            Entry::parse("b2", "fix: handle empty diff"),
+            Entry::parse("c3", "chore: bump deps"),
+            Entry::parse(
+                "d4",
+                "feat(cli)!: rename --vendor\n\nBREAKING CHANGE: use --provider instead",
+            ),
+            Entry::parse("e5", "feat: add config"),
+            Entry::parse("f6", "Update README"),
+            Entry::parse("g7", "i18n: translate menu"),
+        ];
+
+        let markdown = render("[Unreleased]", &entries);
+        assert_eq!(
+            markdown,
+            "## [Unreleased]
+
+### ⚠ BREAKING CHANGES
+
+- **cli:** use --provider instead (d4)
+
+### Added
+
+- add config (e5)
+- **api:** add login (a1)
+- **cli:** rename --vendor (d4)
+
+### Changed
+
+- Update README (f6)
+
+### Fixed
+
+- handle empty diff (b2)
+
+### Other
+
+- translate menu (g7)
+"
+        );
+    }
+}
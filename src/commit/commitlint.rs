@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::commit::rules::Rules;
+
+/// commitlint config files in the order commitlint itself looks them up, `package.json` first
+const CONFIG_FILES: [&str; 5] = [
+    "package.json",
+    ".commitlintrc",
+    ".commitlintrc.json",
+    ".commitlintrc.yaml",
+    ".commitlintrc.yml",
+];
+
+/// JavaScript/TypeScript configs need node to evaluate, they are only reported
+const SCRIPT_CONFIG_FILES: [&str; 6] = [
+    "commitlint.config.js",
+    "commitlint.config.cjs",
+    "commitlint.config.mjs",
+    "commitlint.config.ts",
+    ".commitlintrc.js",
+    ".commitlintrc.cjs",
+];
+
+/// Types of `@commitlint/config-conventional`
+const CONVENTIONAL_TYPES: [&str; 11] = [
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+];
+
+/// Loads the commitlint rules of the repository at `root`, `None` if it has no JSON/YAML commitlint config
+pub fn load(root: &Path) -> Result<Option<Rules>> {
+    for name in CONFIG_FILES {
+        let path = root.join(name);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        let config: Value = if name == "package.json" {
+            // most package.json files have nothing to do with commitlint, a broken one must not stop gitbuddy
+            let package: Value = match serde_json::from_str(&content) {
+                Ok(package) => package,
+                Err(e) => {
+                    eprintln!("Skipping invalid {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            match package.get("commitlint") {
+                Some(config) => config.clone(),
+                None => continue,
+            }
+        } else {
+            // YAML is a superset of JSON, so one parser covers every `.commitlintrc*` flavour
+            serde_norway::from_str(&content).map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))?
+        };
+
+        return Ok(Some(parse_config(&config)));
+    }
+
+    if let Some(name) = SCRIPT_CONFIG_FILES.iter().find(|name| root.join(name).exists()) {
+        eprintln!("Found {}, only JSON/YAML commitlint configs are supported", name);
+    }
+
+    Ok(None)
+}
+
+/// Converts a commitlint config into rules, applying `@commitlint/config-conventional` when extended
+fn parse_config(config: &Value) -> Rules {
+    let extends_conventional = match &config["extends"] {
+        Value::String(name) => name.contains("config-conventional"),
+        Value::Array(names) => names
+            .iter()
+            .any(|name| name.as_str().is_some_and(|n| n.contains("config-conventional"))),
+        _ => false,
+    };
+
+    let mut rules = Rules::default();
+    if extends_conventional {
+        rules.types = CONVENTIONAL_TYPES.iter().map(|s| s.to_string()).collect();
+        rules.header_max_length = Some(100);
+        rules.body_max_line_length = Some(100);
+        rules.subject_full_stop = true;
+    }
+
+    let empty = serde_json::Map::new();
+    for (name, rule) in config["rules"].as_object().unwrap_or(&empty) {
+        // commitlint rules are `[level, applicable, value]`, level 0 disables the rule
+        let level = rule[0].as_u64().unwrap_or(0);
+        let always = rule[1].as_str().unwrap_or("always") == "always";
+        let value = &rule[2];
+
+        match name.as_str() {
+            "type-enum" => rules.types = if level > 0 && always { strings(value) } else { vec![] },
+            "scope-enum" => rules.scopes = if level > 0 && always { strings(value) } else { vec![] },
+            "scope-empty" => rules.scope_required = level > 0 && !always,
+            "subject-full-stop" => rules.subject_full_stop = level > 0 && !always,
+            "header-max-length" => rules.header_max_length = value.as_u64().filter(|_| level > 0).map(|n| n as usize),
+            "body-max-line-length" => {
+                rules.body_max_line_length = value.as_u64().filter(|_| level > 0).map(|n| n as usize)
+            }
+            _ => {}
+        }
+    }
+
+    rules
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_config() {
+        let config: Value = serde_json::from_str(
+            r#"{
+                "extends": ["@commitlint/config-conventional"],
+                "rules": {
+                    "type-enum": [2, "always", ["feat", "fix", "chore"]],
+                    "scope-enum": [2, "always", ["web", "api"]],
+                    "header-max-length": [2, "always", 72],
+                    "body-max-line-length": [0, "always", 100]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let rules = parse_config(&config);
+        assert_eq!(rules.types, vec!["feat", "fix", "chore"]);
+        assert_eq!(rules.scopes, vec!["web", "api"]);
+        assert_eq!(rules.header_max_length, Some(72));
+        assert_eq!(rules.body_max_line_length, None);
+        assert!(rules.subject_full_stop);
+    }
+
+    #[test]
+    fn test_load() {
+        let root = std::env::temp_dir().join(format!("gitbuddy-commitlint-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join(".commitlintrc.json"),
+            r#"{"rules": {"type-enum": [2, "always", ["fix"]]}}"#,
+        )
+        .unwrap();
+
+        // a broken package.json is skipped
+        fs::write(root.join("package.json"), "{ not json").unwrap();
+        assert_eq!(load(&root).unwrap().unwrap().types, vec!["fix"]);
+
+        // like commitlint, the `commitlint` key of package.json wins
+        fs::write(
+            root.join("package.json"),
+            r#"{"commitlint": {"rules": {"type-enum": [2, "always", ["feat"]]}}}"#,
+        )
+        .unwrap();
+        let types = load(&root).unwrap().unwrap().types;
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(types, vec!["feat"]);
+    }
+
+    #[test]
+    fn test_parse_yaml_config() {
+        let config: Value =
+            serde_norway::from_str("rules:\n  type-enum: [2, always, [feat, fix]]\n  scope-empty: [2, never]\n")
+                .unwrap();
+
+        let rules = parse_config(&config);
+        assert_eq!(rules.types, vec!["feat", "fix"]);
+        assert!(rules.scope_required);
+        assert_eq!(rules.header_max_length, None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::commit::rules::Violation;
+use crate::commit::CommitMessage;
+
+/// Writing systems that can be told apart reliably without a language model
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Han,
+    Kana,
+    Hangul,
+    Cyrillic,
+}
+
+/// Expands common language codes such as `zh` or `en` to the names used in prompts
+pub fn normalize(language: &str) -> String {
+    let name = match language.trim().to_lowercase().as_str() {
+        "en" | "en-us" | "en-gb" | "english" => "English",
+        "zh" | "zh-cn" | "zh-hans" | "cn" | "chinese" => "Chinese (Simplified)",
+        "zh-tw" | "zh-hk" | "zh-hant" => "Chinese (Traditional)",
+        "ja" | "jp" | "japanese" => "Japanese",
+        "ko" | "kr" | "korean" => "Korean",
+        "ru" | "russian" => "Russian",
+        "uk" | "ukrainian" => "Ukrainian",
+        "de" | "german" => "German",
+        "fr" | "french" => "French",
+        "es" | "spanish" => "Spanish",
+        _ => return language.trim().to_string(),
+    };
+    name.to_string()
+}
+
+fn expected_script(language: &str) -> Option<Script> {
+    let language = normalize(language);
+    if language.starts_with("Chinese") {
+        Some(Script::Han)
+    } else if language == "Japanese" {
+        Some(Script::Kana)
+    } else if language == "Korean" {
+        Some(Script::Hangul)
+    } else if language == "Russian" || language == "Ukrainian" {
+        Some(Script::Cyrillic)
+    } else if language == "English" {
+        Some(Script::Latin)
+    } else {
+        // other Latin based languages can not be distinguished from English by script alone
+        None
+    }
+}
+
+fn script(c: char) -> Option<Script> {
+    match c {
+        'a'..='z' | 'A'..='Z' => Some(Script::Latin),
+        '\u{3040}'..='\u{30ff}' => Some(Script::Kana),
+        '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => Some(Script::Han),
+        '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' => Some(Script::Hangul),
+        '\u{0400}'..='\u{04ff}' => Some(Script::Cyrillic),
+        _ => None,
+    }
+}
+
+/// Warns when the subject and body are not written in the expected language.
+/// The Conventional Commits prefix and the footers are ignored, they stay in English by convention.
+pub fn check(message: &str, language: &str) -> Option<Violation> {
+    let expected = expected_script(language)?;
+    let message = CommitMessage::parse(message);
+    let text = format!("{} {}", message.subject(), message.body.as_deref().unwrap_or_default());
+
+    let scripts: Vec<Script> = text.chars().filter_map(script).collect();
+    if scripts.is_empty() {
+        return None;
+    }
+
+    let matches = match expected {
+        // code identifiers and English technical terms are common in any language, so only require presence
+        Script::Han | Script::Hangul | Script::Cyrillic => scripts.contains(&expected),
+        // Japanese mixes kana and kanji
+        Script::Kana => scripts.contains(&Script::Kana),
+        Script::Latin => scripts.iter().all(|s| *s == Script::Latin),
+    };
+
+    if matches {
+        None
+    } else {
+        Some(Violation {
+            rule: "language",
+            message: format!("message does not look like it is written in {}", normalize(language)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert!(check("feat(api): 增加登录接口", "zh").is_none());
+        assert!(check("feat(api): add login endpoint", "zh").is_some());
+        assert!(check("feat(api): add login endpoint", "English").is_none());
+        assert!(check("feat(api): 增加 `login` 接口", "en").is_some());
+        assert!(check("feat: ajoute la connexion", "fr").is_none());
+        assert_eq!(normalize("zh-CN"), "Chinese (Simplified)");
+        assert_eq!(normalize("Esperanto"), "Esperanto");
+    }
+}
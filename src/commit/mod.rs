@@ -0,0 +1,159 @@
+pub mod commitlint;
+pub mod language;
+pub mod rules;
+
+/// A commit message split into header, body and trailing footers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessage {
+    pub header: String,
+    pub body: Option<String>,
+    pub footers: Vec<(String, String)>,
+}
+
+/// The Conventional Commits view of a header: `<type>[(scope)][!]: <description>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conventional {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+impl CommitMessage {
+    pub fn parse(message: &str) -> Self {
+        let message = message.trim();
+        let (header, rest) = match message.split_once('\n') {
+            Some((header, rest)) => (header.trim().to_string(), rest.trim()),
+            None => (message.to_string(), ""),
+        };
+
+        let paragraphs: Vec<&str> = rest.split("\n\n").map(|p| p.trim()).filter(|p| !p.is_empty()).collect();
+
+        // footers only live in the last paragraph, and every line of it must look like one
+        let footers = paragraphs
+            .last()
+            .map(|last| last.lines().map(parse_footer).collect::<Option<Vec<_>>>())
+            .unwrap_or_default();
+
+        let (body, footers) = match footers {
+            Some(footers) if !footers.is_empty() => (paragraphs[..paragraphs.len() - 1].join("\n\n"), footers),
+            _ => (paragraphs.join("\n\n"), vec![]),
+        };
+
+        CommitMessage {
+            header,
+            body: if body.is_empty() { None } else { Some(body) },
+            footers,
+        }
+    }
+
+    /// Conventional Commits view of the header, `None` if it does not follow the format
+    pub fn conventional(&self) -> Option<Conventional> {
+        let mut conventional = Conventional::parse(&self.header)?;
+        conventional.breaking |= self
+            .footers
+            .iter()
+            .any(|(token, _)| token == "BREAKING CHANGE" || token == "BREAKING-CHANGE");
+        Some(conventional)
+    }
+
+    /// The subject line without the conventional `type(scope):` prefix
+    pub fn subject(&self) -> &str {
+        match self.header.split_once(": ") {
+            Some((_, subject)) if Conventional::parse(&self.header).is_some() => subject.trim(),
+            _ => &self.header,
+        }
+    }
+}
+
+impl Conventional {
+    pub fn parse(header: &str) -> Option<Self> {
+        let (prefix, description) = header.split_once(": ")?;
+        let (prefix, breaking) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?.to_string())),
+            None => (prefix, None),
+        };
+
+        if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return None;
+        }
+        if scope.as_ref().is_some_and(|s| s.is_empty() || s.contains(['(', ')'])) {
+            return None;
+        }
+
+        Some(Conventional {
+            kind: kind.to_string(),
+            scope,
+            breaking,
+            description: description.trim().to_string(),
+        })
+    }
+
+    /// Formats the header back into `<type>[(scope)][!]: <description>`
+    pub fn header(&self) -> String {
+        let scope = self.scope.as_ref().map(|s| format!("({})", s)).unwrap_or_default();
+        let breaking = if self.breaking { "!" } else { "" };
+        format!("{}{}{}: {}", self.kind, scope, breaking, self.description)
+    }
+}
+
+/// Parses a git trailer / conventional footer line such as `Signed-off-by: Name <mail>` or `Refs #123`
+fn parse_footer(line: &str) -> Option<(String, String)> {
+    if let Some(value) = line.strip_prefix("BREAKING CHANGE: ") {
+        return Some(("BREAKING CHANGE".to_string(), value.to_string()));
+    }
+
+    let (token, value) = line.split_once(": ").or_else(|| line.split_once(" #"))?;
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some((token.to_string(), value.trim().to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_conventional() {
+        let header = Conventional::parse("feat(api)!: send an email").unwrap();
+        assert_eq!(header.kind, "feat");
+        assert_eq!(header.scope.as_deref(), Some("api"));
+        assert!(header.breaking);
+        assert_eq!(header.description, "send an email");
+        assert_eq!(header.header(), "feat(api)!: send an email");
+
+        assert!(Conventional::parse("Update README").is_none());
+        assert!(Conventional::parse("fix(): empty scope").is_none());
+        assert!(Conventional::parse("Merge branch 'main': conflicts").is_none());
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let message = CommitMessage::parse(
+            "fix: prevent racing of requests\n\nIntroduce a request id.\n\nRemove timeouts.\n\nReviewed-by: Z\nRefs #123\n",
+        );
+        assert_eq!(message.header, "fix: prevent racing of requests");
+        assert_eq!(
+            message.body.as_deref(),
+            Some("Introduce a request id.\n\nRemove timeouts.")
+        );
+        assert_eq!(
+            message.footers,
+            vec![
+                ("Reviewed-by".to_string(), "Z".to_string()),
+                ("Refs".to_string(), "123".to_string())
+            ]
+        );
+        assert_eq!(message.subject(), "prevent racing of requests");
+
+        let message = CommitMessage::parse("chore!: drop Node 6\n\nBREAKING CHANGE: use newer features.");
+        assert!(message.body.is_none());
+        assert!(message.conventional().unwrap().breaking);
+    }
+}
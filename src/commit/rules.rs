@@ -0,0 +1,252 @@
+use std::fmt::{Display, Formatter};
+
+use crate::commit::{language, CommitMessage};
+
+/// Constraints a commit message must satisfy, used both to instruct the model and to validate its output
+#[derive(Debug, Default, Clone)]
+pub struct Rules {
+    /// allowed types, empty allows any type
+    pub types: Vec<String>,
+    /// the scope every commit must use, e.g. inferred from the staged package
+    pub required_scope: Option<String>,
+    /// allowed scopes, empty allows any scope
+    pub scopes: Vec<String>,
+    /// a scope must be present
+    pub scope_required: bool,
+    pub header_max_length: Option<usize>,
+    pub body_max_line_length: Option<usize>,
+    /// the subject must not end with a full stop
+    pub subject_full_stop: bool,
+    /// language the subject and body are written in
+    pub language: Option<String>,
+}
+
+/// A single broken rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.message, self.rule)
+    }
+}
+
+impl Rules {
+    /// Rules that require the message to follow the Conventional Commits header format
+    fn requires_conventional(&self) -> bool {
+        !self.types.is_empty() || self.required_scope.is_some() || !self.scopes.is_empty() || self.scope_required
+    }
+
+    /// Instructions describing the rules, appended to the system prompt
+    pub fn instructions(&self) -> Vec<String> {
+        let mut instructions = vec![];
+
+        if !self.types.is_empty() {
+            instructions.push(format!("The type MUST be one of: {}.", self.types.join(", ")));
+        }
+
+        if let Some(scope) = &self.required_scope {
+            instructions.push(format!("The scope MUST be `{}`.", scope));
+        } else if !self.scopes.is_empty() {
+            let optional = if self.scope_required { "" } else { ", if any," };
+            instructions.push(format!(
+                "The scope{} must be one of: {}.",
+                optional,
+                self.scopes.join(", ")
+            ));
+        } else if self.scope_required {
+            instructions.push("A scope is required.".to_string());
+        }
+
+        if let Some(max) = self.header_max_length {
+            instructions.push(format!("The first line must not be longer than {} characters.", max));
+        }
+        if let Some(max) = self.body_max_line_length {
+            instructions.push(format!("Body lines must not be longer than {} characters.", max));
+        }
+        if self.subject_full_stop {
+            instructions.push("The subject must not end with a full stop.".to_string());
+        }
+        if let Some(lang) = &self.language {
+            instructions.push(format!(
+                "Write the subject and body in {}. Keep type keywords (such as feat, fix, docs), scopes and \
+                 trailer tokens in English.",
+                language::normalize(lang)
+            ));
+        }
+
+        instructions
+    }
+
+    pub fn validate(&self, text: &str) -> Vec<Violation> {
+        let mut violations = vec![];
+        let message = CommitMessage::parse(text);
+
+        if let Some(max) = self.header_max_length {
+            let length = message.header.chars().count();
+            if length > max {
+                violations.push(Violation {
+                    rule: "header-max-length",
+                    message: format!("header is {} characters, at most {} allowed", length, max),
+                });
+            }
+        }
+
+        if let (Some(max), Some(body)) = (self.body_max_line_length, &message.body) {
+            if let Some(line) = body.lines().find(|line| line.chars().count() > max) {
+                violations.push(Violation {
+                    rule: "body-max-line-length",
+                    message: format!("body line `{}` is longer than {} characters", line, max),
+                });
+            }
+        }
+
+        if let Some(lang) = &self.language {
+            violations.extend(language::check(text, lang));
+        }
+
+        if self.subject_full_stop && message.subject().ends_with('.') {
+            violations.push(Violation {
+                rule: "subject-full-stop",
+                message: "subject must not end with a full stop".to_string(),
+            });
+        }
+
+        let conventional = match message.conventional() {
+            Some(conventional) => conventional,
+            None => {
+                if self.requires_conventional() {
+                    violations.push(Violation {
+                        rule: "header-format",
+                        message: format!("header `{}` is not `<type>(<scope>): <subject>`", message.header),
+                    });
+                }
+                return violations;
+            }
+        };
+
+        if !self.types.is_empty() && !self.types.contains(&conventional.kind) {
+            violations.push(Violation {
+                rule: "type-enum",
+                message: format!("type `{}` is not one of: {}", conventional.kind, self.types.join(", ")),
+            });
+        }
+
+        if let Some(required) = &self.required_scope {
+            if conventional.scope.as_ref() != Some(required) {
+                violations.push(Violation {
+                    rule: "scope-required",
+                    message: format!("scope must be `{}`", required),
+                });
+            }
+        }
+
+        match &conventional.scope {
+            Some(scope) if !self.scopes.is_empty() && !self.scopes.contains(scope) => {
+                violations.push(Violation {
+                    rule: "scope-enum",
+                    message: format!("scope `{}` is not one of: {}", scope, self.scopes.join(", ")),
+                });
+            }
+            None if self.scope_required && self.required_scope.is_none() => {
+                violations.push(Violation {
+                    rule: "scope-empty",
+                    message: "scope may not be empty".to_string(),
+                });
+            }
+            _ => {}
+        }
+
+        violations
+    }
+
+    /// Applies the corrections that are safe to make without asking the model again
+    pub fn fix(&self, message: &str) -> String {
+        let parsed = CommitMessage::parse(message);
+        let mut conventional = match parsed.conventional() {
+            Some(conventional) => conventional,
+            None => return message.to_string(),
+        };
+
+        if let Some(required) = &self.required_scope {
+            conventional.scope = Some(required.clone());
+        }
+        if self.subject_full_stop {
+            conventional.description = conventional.description.trim_end_matches('.').to_string();
+        }
+
+        // keep the original header's breaking marker, footers may also imply it
+        conventional.breaking = parsed
+            .header
+            .split_once(':')
+            .is_some_and(|(prefix, _)| prefix.ends_with('!'));
+
+        let header = conventional.header();
+        match message.trim().split_once('\n') {
+            Some((_, rest)) => format!("{}\n{}", header, rest),
+            None => header,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        let rules = Rules {
+            required_scope: Some("web".to_string()),
+            ..Default::default()
+        };
+
+        let violations = rules.validate("feat(api): add login");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "scope-required");
+
+        let fixed = rules.fix("feat(api)!: add login\n\nBody text.");
+        assert_eq!(fixed, "feat(web)!: add login\n\nBody text.");
+        assert!(rules.validate(&fixed).is_empty());
+
+        assert_eq!(rules.validate("add login")[0].rule, "header-format");
+    }
+
+    #[test]
+    fn test_scope_enum() {
+        let rules = Rules {
+            scopes: vec!["web".to_string(), "api".to_string()],
+            ..Default::default()
+        };
+
+        assert!(rules.validate("fix(api): handle timeout").is_empty());
+        assert!(rules.validate("fix: handle timeout").is_empty());
+        assert_eq!(rules.validate("fix(db): handle timeout")[0].rule, "scope-enum");
+    }
+
+    #[test]
+    fn test_commitlint_rules() {
+        let rules = Rules {
+            types: vec!["feat".to_string(), "fix".to_string()],
+            scope_required: true,
+            header_max_length: Some(20),
+            subject_full_stop: true,
+            ..Default::default()
+        };
+
+        let violations: Vec<&str> = rules
+            .validate("docs: update the contributing guide.")
+            .iter()
+            .map(|v| v.rule)
+            .collect();
+        assert_eq!(
+            violations,
+            vec!["header-max-length", "subject-full-stop", "type-enum", "scope-empty"]
+        );
+
+        assert_eq!(rules.fix("fix(ui): typo."), "fix(ui): typo");
+        assert!(rules.validate("fix(ui): typo").is_empty());
+    }
+}
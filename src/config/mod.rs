@@ -2,7 +2,10 @@ use crate::llm::PromptModel;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
+pub mod repo;
 mod storage;
+
+pub(crate) use storage::get_config_dir;
 mod vendor;
 
 /// Update or create configuration for a specific model
@@ -39,6 +42,7 @@ fn create_default_config() -> GlobalConfig {
         default: DefaultConfig {
             default_service: PromptModel::DeepSeek,
             timeout: 30,
+            language: None,
         },
         openai: None,
         deepseek: None,
@@ -49,6 +53,7 @@ fn create_default_config() -> GlobalConfig {
             top_k: 5,
             max_tokens: 1024,
         }),
+        report: ReportConfig::default(),
     }
 }
 
@@ -63,6 +68,8 @@ pub struct GlobalConfig {
     pub deepseek: Option<ModelConfig>,
     pub ollama: Option<ModelConfig>,
     pub model_parameters: Option<ModelParameters>,
+    #[serde(default)]
+    pub report: ReportConfig,
 }
 
 impl GlobalConfig {
@@ -123,6 +130,16 @@ pub struct ModelConfig {
 pub struct DefaultConfig {
     pub default_service: PromptModel,
     pub timeout: u32,
+    /// language of the generated commit messages
+    pub language: Option<String>,
+}
+
+/// Settings of `gitbuddy report`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReportConfig {
+    /// repositories reported along with the current one, `~` is the home directory
+    #[serde(default)]
+    pub repos: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::git::git_toplevel;
+use crate::style::preset::Preset;
+
+const REPO_DIR: &str = ".gitbuddy";
+const REPO_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Repository level configuration, read from `.gitbuddy/config.toml` in the repository root
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RepoConfig {
+    /// commit message style used when `--style` is not given
+    pub style: Option<Preset>,
+    /// language of the commit messages, overrides the global config
+    pub language: Option<String>,
+    #[serde(default)]
+    pub scope: ScopeConfig,
+    #[serde(default)]
+    pub pr: PrConfig,
+    #[serde(default)]
+    pub branch: BranchConfig,
+}
+
+/// Settings of `gitbuddy pr`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PrConfig {
+    /// branch pull requests are compared against, defaults to the default branch of `origin`
+    pub base: Option<String>,
+}
+
+/// Settings of `gitbuddy branch`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BranchConfig {
+    /// branch name with `{type}`, `{ticket}` and `{slug}` placeholders, `{type}/{slug}` by default
+    pub pattern: Option<String>,
+}
+
+/// How the Conventional Commits scope is derived from the staged paths
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScopeConfig {
+    /// detect scopes from Cargo workspace members and package.json workspaces
+    #[serde(default)]
+    pub auto: bool,
+    /// explicit path globs to scope names, the first matching entry wins
+    #[serde(default)]
+    pub mapping: Vec<ScopeMapping>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopeMapping {
+    pub path: String,
+    pub scope: String,
+}
+
+impl RepoConfig {
+    /// Load the config of the current repository, falling back to defaults when there is none
+    pub fn load() -> Result<Self> {
+        match repo_dir() {
+            Some(dir) => Self::load_from(&dir.join(REPO_CONFIG_FILE_NAME)),
+            None => Ok(RepoConfig::default()),
+        }
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(|e| anyhow!("Invalid {}: {}", path.display(), e)),
+            Err(_) => Ok(RepoConfig::default()),
+        }
+    }
+}
+
+/// get the `.gitbuddy` dir of the current repository
+pub fn repo_dir() -> Option<PathBuf> {
+    git_toplevel().map(|root| root.join(REPO_DIR))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_config() {
+        let content = r#"
+style = "gitmoji"
+
+[scope]
+auto = true
+
+[[scope.mapping]]
+path = "crates/core/**"
+scope = "core"
+        "#;
+
+        let cfg: RepoConfig = toml::from_str(content).unwrap();
+        assert_eq!(cfg.style, Some(Preset::Gitmoji));
+        assert!(cfg.scope.auto);
+        assert_eq!(cfg.scope.mapping[0].scope, "core");
+
+        let cfg: RepoConfig = toml::from_str("").unwrap();
+        assert!(!cfg.scope.auto);
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Result};
+
+use crate::ai;
+use crate::ai::git::{
+    change_summary, git_commit_changes, git_commit_diff, git_commit_message, git_log_commits, git_range_changes,
+    git_range_diff, git_resolve_range, git_rev_parse,
+};
+use crate::commit::language;
+use crate::config::repo::RepoConfig;
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+use crate::prompt::budget::{estimate_tokens, truncate_diff, DEFAULT_DIFF_TOKENS};
+
+const EXPLAIN_PROMPT: &str = r#"You are a senior engineer explaining unfamiliar history to a teammate. You get the
+commit messages and the diff of one commit or a range of commits.
+
+Answer in {language} with GitHub flavored Markdown in exactly these sections:
+## What changed
+The changes in plain language, grouped by area, without repeating the diff line by line.
+## Why it likely changed
+The probable motivation, based on the commit messages and the code. Say so when it is a guess.
+## Risk areas
+What could break or deserves a closer look, or "None" when nothing stands out.
+
+Parts of long diffs are omitted, do not speculate about omitted lines."#;
+
+/// Share of the token budget the commit messages may take, the diff gets the rest
+const LOG_SHARE: usize = 4;
+
+/// Options of `gitbuddy explain`
+pub struct Options {
+    /// a commit, or a range like `main..HEAD`
+    pub target: String,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Explain what a commit or range of commits did, why and what is risky about it
+pub fn handler(options: Options) -> Result<()> {
+    let (commits, diff) = if options.target.contains("..") {
+        let (from, to) = git_resolve_range(&options.target)?;
+        let commits = git_log_commits(Some(&from), &to);
+        let diff = format!(
+            "{}\n{}",
+            change_summary(&git_range_changes(&from, &to)),
+            git_range_diff(&from, &to)
+        );
+        (commits, diff)
+    } else {
+        let hash = git_rev_parse(&options.target).ok_or_else(|| anyhow!("`{}` is not a commit", options.target))?;
+        let message = git_commit_message(&hash).unwrap_or_default();
+        let diff = format!(
+            "{}\n{}",
+            change_summary(&git_commit_changes(&hash)),
+            git_commit_diff(&hash)
+        );
+        (vec![(hash, message)], diff)
+    };
+    if commits.is_empty() {
+        return Err(anyhow!("No commits in `{}`", options.target));
+    }
+
+    let repo_config = RepoConfig::load()?;
+    let language = ai::configured_language(options.language.clone(), &repo_config);
+    let system_prompt = EXPLAIN_PROMPT.replace(
+        "{language}",
+        &language::normalize(language.as_deref().unwrap_or("English")),
+    );
+    let user_message = context(&commits, &diff, DEFAULT_DIFF_TOKENS);
+
+    let result = llm::request_with_progress(
+        &format!("Explaining {} commits", commits.len()),
+        &[ChatMessage::system(&system_prompt), ChatMessage::user(&user_message)],
+        options.vendor,
+        options.model.clone(),
+    )?;
+    println!("{}", result.message().trim());
+    Ok(())
+}
+
+/// Fits the commit messages and the diff into `max_tokens`. Full messages are kept while they take less than
+/// a quarter of the budget, otherwise only the subjects, and the diff is truncated to what is left.
+fn context(commits: &[(String, String)], diff: &str, max_tokens: usize) -> String {
+    let full: Vec<String> = commits
+        .iter()
+        .map(|(hash, message)| format!("commit {}\n{}\n", hash, message.trim()))
+        .collect();
+    let mut log = full.join("\n");
+    if estimate_tokens(&log) > max_tokens / LOG_SHARE {
+        let subjects: Vec<String> = commits
+            .iter()
+            .map(|(hash, message)| format!("{} {}", hash, message.lines().next().unwrap_or_default()))
+            .collect();
+        log = subjects.join("\n");
+    }
+
+    let remaining = max_tokens.saturating_sub(estimate_tokens(&log));
+    format!("Commits:\n{}\n\nDiff:\n{}", log, truncate_diff(diff, remaining))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_context() {
+        let commits = vec![
+            ("a1".to_string(), "feat: add login\n\nUses sessions.".to_string()),
+            ("b2".to_string(), "fix: typo".to_string()),
+        ];
+        let diff = "diff --git a/x b/x\n+short\n";
+
+        let text = context(&commits, diff, 1000);
+        assert!(text.starts_with("Commits:\ncommit a1\nfeat: add login\n\nUses sessions.\n"));
+        assert!(text.ends_with(diff));
+
+        let text = context(&commits, diff, 20);
+        assert!(text.starts_with("Commits:\na1 feat: add login\nb2 fix: typo\n\nDiff:\n"));
+    }
+}
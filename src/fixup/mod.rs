@@ -0,0 +1,176 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai::git::{
+    git_blame_commits, git_log_commits, git_merge_base, git_rebase_autosquash, git_stage_patch, git_upstream,
+};
+use crate::split;
+use crate::split::patch;
+use crate::split::patch::Hunk;
+
+/// The commit a staged hunk belongs to
+#[derive(Debug, PartialEq, Eq)]
+enum Target {
+    /// index of the commit in the stack
+    Commit(usize),
+    /// the lines were last changed by several commits, the short hashes of those in the stack
+    Ambiguous(Vec<String>),
+    /// the lines were last changed before the stack, by commits that are already pushed
+    Outside,
+    /// new, deleted and binary files and mode changes have no lines to blame
+    Unsupported,
+}
+
+/// Options of `gitbuddy fixup`
+pub struct Options {
+    /// where the stack of unpushed commits starts, the upstream branch by default
+    pub base: Option<String>,
+    /// run `git rebase -i --autosquash` after committing
+    pub rebase: bool,
+    /// only show which commit each hunk belongs to
+    pub dry_run: bool,
+}
+
+/// Commit each staged hunk as `fixup!` of the commit of the stack that last changed its lines
+pub fn handler(options: Options) -> Result<()> {
+    let base = options
+        .base
+        .clone()
+        .or_else(git_upstream)
+        .ok_or_else(|| anyhow!("No upstream branch, choose where the stack starts with --base"))?;
+    let merge_base = git_merge_base(&base, "HEAD").ok_or_else(|| anyhow!("No common ancestor with `{}`", base))?;
+    let stack = git_log_commits(Some(&merge_base), "HEAD");
+    if stack.is_empty() {
+        return Err(anyhow!("No commits between `{}` and HEAD to fix up", base));
+    }
+
+    let files = patch::parse(&git_stage_patch());
+    let hunks = patch::hunks(&files);
+    if hunks.is_empty() {
+        return Err(anyhow!("No changes staged"));
+    }
+
+    let mut targets: Vec<Vec<Hunk>> = vec![vec![]; stack.len()];
+    let mut skipped = 0;
+    for hunk in &hunks {
+        let target = match patch::touched_lines(&files, hunk) {
+            Some(lines) if !lines.is_empty() => {
+                classify(&git_blame_commits("HEAD", &files[hunk.file].path, &lines), &stack)
+            }
+            _ => Target::Unsupported,
+        };
+
+        let label = patch::label(&files, hunk);
+        match target {
+            Target::Commit(n) => {
+                println!("{}  {} {}", label, stack[n].0.yellow(), subject(&stack[n].1));
+                targets[n].push(*hunk);
+            }
+            Target::Ambiguous(hashes) => {
+                skipped += 1;
+                println!("{}  {} {}", label, "ambiguous:".red(), hashes.join(", "));
+            }
+            Target::Outside => {
+                skipped += 1;
+                println!(
+                    "{}  {}",
+                    label,
+                    "last changed before the stack".truecolor(128, 128, 128)
+                );
+            }
+            Target::Unsupported => {
+                skipped += 1;
+                println!("{}  {}", label, "new, deleted or binary file".truecolor(128, 128, 128));
+            }
+        }
+    }
+
+    let commits: Vec<(Vec<Hunk>, String)> = targets
+        .into_iter()
+        .zip(&stack)
+        .filter(|(hunks, _)| !hunks.is_empty())
+        .map(|(hunks, (_, message))| (hunks, format!("fixup! {}", subject(message))))
+        .collect();
+    if commits.is_empty() {
+        println!("Nothing to fix up, {} hunks stay staged", skipped);
+        return Ok(());
+    }
+    if options.dry_run {
+        return Ok(());
+    }
+
+    // the hunks without a target stay staged
+    split::commit_hunks(&files, &commits, false)?;
+    if skipped > 0 {
+        println!("{} hunks without a clear target stay staged", skipped);
+    }
+
+    if options.rebase {
+        git_rebase_autosquash(&merge_base)?;
+        println!("{}", "Fixups squashed into their commits".green().bold());
+    } else {
+        println!(
+            "{} {} fixup commits, squash them with `git rebase -i --autosquash {}`",
+            "Created".green().bold(),
+            commits.len(),
+            base
+        );
+    }
+    Ok(())
+}
+
+/// Picks the commit of the stack that last changed all the blamed lines
+fn classify(blamed: &[String], stack: &[(String, String)]) -> Target {
+    let mut found: Vec<usize> = vec![];
+    let mut outside = false;
+    for hash in blamed {
+        match stack.iter().position(|(short, _)| hash.starts_with(short.as_str())) {
+            Some(n) if !found.contains(&n) => found.push(n),
+            Some(_) => {}
+            None => outside = true,
+        }
+    }
+
+    match (found.as_slice(), outside) {
+        ([], true) => Target::Outside,
+        ([], false) => Target::Unsupported,
+        ([n], false) => Target::Commit(*n),
+        (found, outside) => {
+            let mut hashes: Vec<String> = found.iter().map(|n| stack[*n].0.clone()).collect();
+            if outside {
+                hashes.push("earlier commits".to_string());
+            }
+            Target::Ambiguous(hashes)
+        }
+    }
+}
+
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let stack = vec![
+            ("1a2b3c4".to_string(), "feat: add login".to_string()),
+            ("5d6e7f8".to_string(), "fix: typo".to_string()),
+        ];
+        let full = |short: &str| format!("{}{}", short, "0".repeat(33));
+
+        assert_eq!(classify(&[full("5d6e7f8"), full("5d6e7f8")], &stack), Target::Commit(1));
+        assert_eq!(classify(&[full("9999999")], &stack), Target::Outside);
+        assert_eq!(
+            classify(&[full("1a2b3c4"), full("5d6e7f8")], &stack),
+            Target::Ambiguous(vec!["1a2b3c4".to_string(), "5d6e7f8".to_string()])
+        );
+        assert_eq!(
+            classify(&[full("1a2b3c4"), full("9999999")], &stack),
+            Target::Ambiguous(vec!["1a2b3c4".to_string(), "earlier commits".to_string()])
+        );
+        assert_eq!(classify(&[], &stack), Target::Unsupported);
+    }
+}
@@ -0,0 +1,236 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use colored::Colorize;
+
+use crate::ai;
+use crate::ai::git::git_hooks_dir;
+use crate::lint;
+
+/// Marks the hook scripts written by gitbuddy
+const MARKER: &str = "# installed by gitbuddy";
+/// Suffix of a hook that existed before installing, it is run first and restored on uninstall
+const CHAINED_SUFFIX: &str = ".chained";
+
+/// Git hooks gitbuddy can install
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Hook {
+    /// write a generated message into the commit message file of `git commit`
+    #[clap(name = "prepare-commit-msg")]
+    PrepareCommitMsg,
+    /// reject commit messages that break the rules of the repository, see `gitbuddy lint`
+    #[clap(name = "commit-msg")]
+    CommitMsg,
+}
+
+impl Hook {
+    fn file_name(&self) -> &'static str {
+        match self {
+            Hook::PrepareCommitMsg => "prepare-commit-msg",
+            Hook::CommitMsg => "commit-msg",
+        }
+    }
+
+    fn script(&self, exe: &Path, fix: bool) -> String {
+        let name = self.file_name();
+        let command = if fix {
+            format!("hook run {} --fix", name)
+        } else {
+            format!("hook run {}", name)
+        };
+        format!(
+            r#"#!/bin/sh
+{MARKER}, `gitbuddy hook uninstall {name}` removes it
+chained="$(dirname "$0")/{name}{CHAINED_SUFFIX}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+exec {exe} {command} "$@"
+"#,
+            exe = shell_quote(&exe.to_string_lossy()),
+        )
+    }
+}
+
+/// Install the hook, keeping an existing hook as chained hook.
+/// `fix` lets the commit-msg hook rewrite the message with the safe fixes.
+pub fn install_handler(hook: Hook, fix: bool) -> Result<()> {
+    if fix && hook == Hook::PrepareCommitMsg {
+        return Err(anyhow!("--fix only applies to the commit-msg hook"));
+    }
+    let (path, chained) = hook_paths(hook)?;
+    let script = hook.script(&std::env::current_exe()?, fix);
+
+    if is_ours(&path) {
+        // installed again with other options, e.g. `--fix`, or from another location of gitbuddy
+        if fs::read_to_string(&path)? == script {
+            println!("{} is already installed", hook.file_name());
+        } else {
+            fs::write(&path, script)?;
+            println!("{} {}", "Updated".green(), path.display());
+        }
+        return Ok(());
+    }
+    if path.exists() {
+        if chained.exists() {
+            return Err(anyhow!(
+                "{} and {} both exist, remove one of them first",
+                path.display(),
+                chained.display()
+            ));
+        }
+        fs::rename(&path, &chained)?;
+        println!("Existing hook moved to {}, it runs before gitbuddy", chained.display());
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, script)?;
+    make_executable(&path)?;
+
+    println!("{} {}", "Installed".green(), path.display());
+    Ok(())
+}
+
+/// Remove the hook and restore the hook it was chaining
+pub fn uninstall_handler(hook: Hook) -> Result<()> {
+    let (path, chained) = hook_paths(hook)?;
+
+    if !path.exists() {
+        println!("{} is not installed", hook.file_name());
+        return Ok(());
+    }
+    if !is_ours(&path) {
+        return Err(anyhow!(
+            "{} was not installed by gitbuddy, leaving it alone",
+            path.display()
+        ));
+    }
+
+    fs::remove_file(&path)?;
+    if chained.exists() {
+        fs::rename(&chained, &path)?;
+        println!("Restored the previous hook {}", path.display());
+    }
+
+    println!("{} {}", "Uninstalled".green(), path.display());
+    Ok(())
+}
+
+/// Print which hooks are installed
+pub fn status_handler() -> Result<()> {
+    for hook in Hook::value_variants() {
+        let (path, chained) = hook_paths(*hook)?;
+
+        let status = if is_ours(&path) {
+            if chained.exists() {
+                "installed, chaining the previous hook".green()
+            } else {
+                "installed".green()
+            }
+        } else if path.exists() {
+            "another hook is installed".yellow()
+        } else {
+            "not installed".normal()
+        };
+        println!("{:<20} {}  ({})", hook.file_name(), status, path.display());
+    }
+    Ok(())
+}
+
+/// Entry point of the installed hook scripts, `args` are the arguments git passes to the hook
+pub fn run_handler(hook: Hook, fix: bool, args: &[String]) -> Result<()> {
+    match hook {
+        Hook::PrepareCommitMsg => {
+            let file = args
+                .first()
+                .ok_or_else(|| anyhow!("prepare-commit-msg needs the message file"))?;
+            prepare_commit_msg(Path::new(file), args.get(1).map(|s| s.as_str()))
+        }
+        // the staged paths are those of the commit, so the scope rules apply as on generation
+        Hook::CommitMsg => lint::handler(args.first().map(Path::new), fix, true, None, None),
+    }
+}
+
+fn prepare_commit_msg(file: &Path, source: Option<&str>) -> Result<()> {
+    if !should_generate(source) {
+        return Ok(());
+    }
+
+    let options = ai::Options {
+        candidates: 1,
+        ..Default::default()
+    };
+    // a failing generation must not block the commit, the user can still write the message
+    let message = match ai::generate_message(&options) {
+        Ok(message) => message,
+        Err(e) => {
+            eprintln!("gitbuddy: {}", e);
+            return Ok(());
+        }
+    };
+
+    // keep what git prepared, like the template or the status comments, below the message
+    let existing = fs::read_to_string(file).unwrap_or_default();
+    fs::write(file, format!("{}\n{}", message.trim(), existing))?;
+    Ok(())
+}
+
+/// Only plain `git commit` and commit templates get a generated message. Messages given with
+/// `-m`/`-F`, merges, squashes and amends or `-c`/`-C` already have one.
+fn should_generate(source: Option<&str>) -> bool {
+    matches!(source, None | Some("template"))
+}
+
+fn hook_paths(hook: Hook) -> Result<(PathBuf, PathBuf)> {
+    let dir = git_hooks_dir().ok_or_else(|| anyhow!("Not git directory"))?;
+    let path = dir.join(hook.file_name());
+    let chained = dir.join(format!("{}{}", hook.file_name(), CHAINED_SUFFIX));
+    Ok((path, chained))
+}
+
+fn is_ours(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(MARKER))
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hook_script() {
+        let script = Hook::PrepareCommitMsg.script(Path::new("/opt/my tools/gitbuddy"), false);
+        assert!(script.contains(MARKER));
+        assert!(script.contains("exec '/opt/my tools/gitbuddy' hook run prepare-commit-msg \"$@\""));
+        assert!(script.contains("prepare-commit-msg.chained"));
+        let script = Hook::CommitMsg.script(Path::new("gitbuddy"), true);
+        assert!(script.contains("exec 'gitbuddy' hook run commit-msg --fix \"$@\""));
+
+        assert!(should_generate(None));
+        assert!(should_generate(Some("template")));
+        assert!(!should_generate(Some("message")));
+        assert!(!should_generate(Some("merge")));
+        assert!(!should_generate(Some("squash")));
+        assert!(!should_generate(Some("commit")));
+    }
+}
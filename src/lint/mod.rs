@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai;
+use crate::ai::editor::strip_comments;
+use crate::ai::git::git_stage_filenames;
+use crate::commit::rules::{Rules, Violation};
+use crate::config::repo::RepoConfig;
+use crate::style::preset::Preset;
+
+/// Check a commit message against the rules of the repository, read from `file` or stdin.
+/// With `fix` the safe fixes are applied and written back before checking. With `staged_scopes` the scopes are
+/// inferred from the staged paths like on generation, only meaningful for the message of the commit being made.
+pub fn handler(
+    file: Option<&Path>,
+    fix_message: bool,
+    staged_scopes: bool,
+    style: Option<Preset>,
+    language: Option<String>,
+) -> Result<()> {
+    let text = match file {
+        Some(file) if file != Path::new("-") => fs::read_to_string(file)?,
+        _ => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+
+    let mut message = strip_comments(&text);
+    if message.is_empty() {
+        // git aborts empty commits by itself
+        return Ok(());
+    }
+
+    let repo_config = RepoConfig::load()?;
+    let style = style.or(repo_config.style);
+    let options = ai::Options {
+        language,
+        ..Default::default()
+    };
+    let mut rules = ai::configured_rules(&options, &repo_config, style)?;
+    if staged_scopes {
+        ai::apply_inferred_scopes(&mut rules, &git_stage_filenames(), &repo_config, style);
+    }
+
+    if fix_message && !is_ignored(&message) {
+        let fixed = fix(&message, &rules, style);
+        if fixed != message {
+            match file {
+                Some(file) if file != Path::new("-") => fs::write(file, format!("{}\n", fixed))?,
+                _ => println!("{}", fixed),
+            }
+            message = fixed;
+        }
+    }
+
+    let violations = validate(&message, &rules, style);
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("{}", message.lines().next().unwrap_or_default().bold());
+    for violation in &violations {
+        eprintln!("  {} {}", "✖".red(), violation);
+    }
+    Err(anyhow!(
+        "{} problem(s) found, see `gitbuddy lint --help`",
+        violations.len()
+    ))
+}
+
+/// Applies the safe fixes of the rules and the formatting of the style
+pub fn fix(message: &str, rules: &Rules, style: Option<Preset>) -> String {
+    let message = rules.fix(message);
+    match style {
+        Some(style) => style.format(&message),
+        None => message,
+    }
+}
+
+/// Subjects git writes itself, they are never checked, like the default ignores of commitlint
+const IGNORED_PREFIXES: [&str; 8] = [
+    "fixup! ",
+    "squash! ",
+    "amend! ",
+    "Merge ",
+    "Merged ",
+    "Revert \"",
+    "Automatic merge",
+    "Auto-merged ",
+];
+
+/// Whether the message is generated by git, for autosquash, merges and reverts
+fn is_ignored(message: &str) -> bool {
+    let subject = message.trim_start();
+    IGNORED_PREFIXES.iter().any(|prefix| subject.starts_with(prefix))
+}
+
+/// The rules and style conventions the message breaks
+pub fn validate(message: &str, rules: &Rules, style: Option<Preset>) -> Vec<Violation> {
+    if is_ignored(message) {
+        return vec![];
+    }
+
+    let mut violations = rules.validate(message);
+    if let Some(style) = style {
+        // the style checks some of the rules again, report each rule once
+        let mut seen = HashSet::new();
+        violations.extend(style.validate(message));
+        violations.retain(|violation| seen.insert(violation.rule));
+    }
+    violations
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fix_and_validate() {
+        let rules = Preset::Angular.rules();
+        let message = "feat(api): add login.";
+
+        assert!(!validate(message, &rules, Some(Preset::Angular)).is_empty());
+        let fixed = fix(message, &rules, Some(Preset::Angular));
+        assert_eq!(fixed, "feat(api): add login");
+        assert!(validate(&fixed, &rules, Some(Preset::Angular)).is_empty());
+
+        let violations = validate("added login", &rules, None);
+        assert!(violations.iter().any(|v| v.rule == "header-format"));
+    }
+
+    #[test]
+    fn test_validate_reports_each_rule_once() {
+        let rules = Rules {
+            header_max_length: Some(10),
+            ..Preset::Conventional.rules()
+        };
+        let violations = validate("added the login endpoint", &rules, Some(Preset::Conventional));
+        let mut names: Vec<&str> = violations.iter().map(|v| v.rule).collect();
+        assert!(names.len() >= 2, "{:?}", names);
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), violations.len(), "{:?}", violations);
+    }
+
+    #[test]
+    fn test_validate_ignores_git_subjects() {
+        let rules = Preset::Conventional.rules();
+        for message in [
+            "fixup! feat: add login",
+            "squash! fix: typo",
+            "amend! feat: add login\n\nfeat(api): add login",
+            "Merge branch 'main' into feature",
+            "Revert \"feat: add login\"",
+        ] {
+            let violations = validate(message, &rules, Some(Preset::Conventional));
+            assert!(violations.is_empty(), "{}: {:?}", message, violations);
+        }
+        assert!(!validate("fixup: add login", &rules, Some(Preset::Conventional)).is_empty());
+    }
+}
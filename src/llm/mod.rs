@@ -3,13 +3,12 @@ mod openai_compatible_builder;
 
 use crate::config;
 use crate::config::ModelParameters;
-use crate::prompt::Prompt;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::ValueEnum;
 use colored::Colorize;
 use openai_compatible_builder::OpenAICompatibleBuilder;
 use serde::{Deserialize, Serialize};
-use std::io::Write;
+use std::time::{Duration, Instant};
 
 /// Prompt model
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize, Serialize)]
@@ -26,6 +25,11 @@ pub enum PromptModel {
 }
 
 impl PromptModel {
+    /// Whether the chat completions API of the vendor returns several choices for the `n` parameter
+    pub fn supports_choices(&self) -> bool {
+        matches!(self, PromptModel::OpenAI)
+    }
+
     pub fn default_model(&self) -> String {
         match self {
             PromptModel::OpenAI => "gpt-3.5-turbo".to_string(),
@@ -37,12 +41,36 @@ impl PromptModel {
 
 #[derive(Debug)]
 pub struct LLMResult {
-    pub commit_message: String,
+    /// the generated messages, one per requested choice
+    pub choices: Vec<String>,
     pub completion_tokens: i64,
     pub prompt_tokens: i64,
     pub total_tokens: i64,
 }
 
+impl LLMResult {
+    /// The first generated message
+    pub fn message(&self) -> &str {
+        self.choices.first().map(|s| s.as_str()).unwrap_or_default()
+    }
+
+    /// The duration and token usage line printed after a request
+    pub fn usage(&self, duration: Duration) -> String {
+        format!(
+            "duration={:?} - Usage={}(completion={}, prompt={})]",
+            duration, self.total_tokens, self.completion_tokens, self.prompt_tokens
+        )
+    }
+
+    /// Appends the choices of another request and sums the token usage
+    fn merge(&mut self, other: LLMResult) {
+        self.choices.extend(other.choices);
+        self.completion_tokens += other.completion_tokens;
+        self.prompt_tokens += other.prompt_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
 struct RequestsWrap {
     vendor: PromptModel,
     model: String,
@@ -55,55 +83,128 @@ impl RequestsWrap {
     }
 }
 
-pub fn llm_request(
-    diff_content: &str,
+/// A single message of a chat conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: &str) -> Self {
+        ChatMessage {
+            role: "system".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    pub fn user(content: &str) -> Self {
+        ChatMessage {
+            role: "user".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    pub fn assistant(content: &str) -> Self {
+        ChatMessage {
+            role: "assistant".to_string(),
+            content: content.to_string(),
+        }
+    }
+}
+
+pub fn llm_request(messages: &[ChatMessage], vendor: Option<PromptModel>, model: Option<String>) -> Result<LLMResult> {
+    llm_request_choices(messages, vendor, model, 1)
+}
+
+/// Requests a single answer, printing `label` before and the duration and token usage after it
+pub fn request_with_progress(
+    label: &str,
+    messages: &[ChatMessage],
+    vendor: Option<PromptModel>,
+    model: Option<String>,
+) -> Result<LLMResult> {
+    eprintln!("{} by LLM...", label);
+    let start = Instant::now();
+    let result = llm_request(messages, vendor, model)?;
+    eprintln!(
+        "{}  {}",
+        "Completed!".green(),
+        result.usage(start.elapsed()).truecolor(128, 128, 128)
+    );
+    Ok(result)
+}
+
+/// The JSON object in an answer, without the Markdown fence or text the model put around it
+pub fn extract_json(answer: &str) -> Result<&str> {
+    match (answer.find('{'), answer.rfind('}')) {
+        (Some(start), Some(end)) if start < end => Ok(&answer[start..=end]),
+        _ => Err(anyhow!("The model did not answer with JSON:\n{}", answer)),
+    }
+}
+
+/// Requests `n` alternative answers, with the `n` parameter where the vendor supports it and
+/// otherwise with parallel requests. The token usage of all requests is summed.
+pub fn llm_request_choices(
+    messages: &[ChatMessage],
     vendor: Option<PromptModel>,
     model: Option<String>,
-    prompt: Prompt,
+    n: usize,
 ) -> Result<LLMResult> {
     let config = config::get_config()?;
 
     let (model_config, prompt_model) = config.model(vendor).unwrap();
 
     let model = model.unwrap_or(model_config.model.clone());
-    println!("use model: {model}");
-
-    get_commit_message(
-        prompt_model,
-        model.as_str(),
-        model_config.api_key.clone().unwrap_or("".into()).as_str(),
-        diff_content,
-        config.model_params(),
-        prompt,
-    )
+    eprintln!("use model: {model}");
+
+    let api_key = model_config.api_key.clone().unwrap_or("".into());
+    let option = config.model_params();
+
+    if n <= 1 || prompt_model.supports_choices() {
+        return get_commit_message(
+            prompt_model,
+            model.as_str(),
+            api_key.as_str(),
+            messages,
+            option,
+            n.max(1),
+        );
+    }
+
+    let results: Vec<Result<LLMResult>> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..n)
+            .map(|_| {
+                let option = option.clone();
+                s.spawn(|| get_commit_message(prompt_model, model.as_str(), api_key.as_str(), messages, option, 1))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|_| Err(anyhow!("LLM request panicked"))))
+            .collect()
+    });
+
+    let mut results = results.into_iter();
+    let mut combined = results.next().unwrap()?;
+    for result in results {
+        combined.merge(result?);
+    }
+    Ok(combined)
 }
 
 fn get_commit_message(
     vendor: PromptModel,
     model: &str,
     api_key: &str,
-    diff_content: &str,
+    messages: &[ChatMessage],
     option: ModelParameters,
-    prompt: Prompt,
+    n: usize,
 ) -> Result<LLMResult> {
     let builder = OpenAICompatibleBuilder::new(vendor, model, api_key);
 
     // generate http request
-    let m = builder.build(prompt.value().to_string());
-    let result = m.request(diff_content, option)?;
+    let m = builder.build();
+    let result = m.request(messages, option, n)?;
     Ok(result)
 }
-
-pub fn confirm_commit(commit_message: &str) -> bool {
-    println!("--------------------------------------");
-    println!("{}", commit_message.cyan().bold());
-    println!("--------------------------------------");
-    print!("Are you sure you want to commit? (Y/n) ");
-    let mut input = String::new();
-
-    // flush
-    std::io::stdout().flush().unwrap();
-    std::io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    return input.trim() == "y" || input.trim() == "Y" || input.trim() == "";
-}
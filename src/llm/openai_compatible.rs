@@ -1,6 +1,5 @@
 use crate::config::ModelParameters;
-use crate::llm::LLMResult;
-use crate::prompt::Prompt;
+use crate::llm::{ChatMessage, LLMResult};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -10,7 +9,6 @@ use std::time::Duration;
 pub(crate) struct OpenAICompatible {
     pub(crate) url: String,
     pub(crate) model: String,
-    pub(crate) prompt: String,
     pub(crate) api_key: String,
 }
 
@@ -46,41 +44,38 @@ struct OpenAIResponseUsage {
 }
 
 impl OpenAICompatible {
-    pub(crate) fn request(&self, diff_content: &str, option: ModelParameters) -> Result<LLMResult> {
+    pub(crate) fn request(&self, messages: &[ChatMessage], option: ModelParameters, n: usize) -> Result<LLMResult> {
         let client = reqwest::blocking::Client::new();
 
         let api_key = self.api_key.clone();
 
+        let mut body = json!({
+            "model": &self.model,
+            "messages": messages,
+            "options": {
+                "temperature": option.temperature,
+                "top_p": option.top_p,
+                "top_k": option.top_k,
+            },
+            "options": option,
+            "keep_alive": "30m",
+            "max_tokens": option.max_tokens,
+            // "format": {
+            //     "type": "object",
+            //     "properties": {"subject": {"type":"string"}, "scope": {"type":"string"}, "summary": {"type":"string"}},
+            //     "required": ["subject", "scope", "summary"]
+            // },
+        });
+        // only sent when needed, not every compatible API accepts it
+        if n > 1 {
+            body["n"] = json!(n);
+        }
+
         let response = client
             .post(format!("{}/v1/chat/completions", self.url))
             .timeout(Duration::from_secs(120))
             .header("Authorization", format!("Bearer {api_key}",))
-            .json(&json!({
-                "model": &self.model,
-                "messages": [
-                    {
-                        "role": "system",
-                        "content": self.prompt,
-                    },
-                    {
-                        "role": "user",
-                        "content": format!("diff content: \n{diff_content}"),
-                    }
-                ],
-                "options": {
-                    "temperature": option.temperature,
-                    "top_p": option.top_p,
-                    "top_k": option.top_k,
-                },
-                "options": option,
-                "keep_alive": "30m",
-                "max_tokens": option.max_tokens,
-                // "format": {
-                //     "type": "object",
-                //     "properties": {"subject": {"type":"string"}, "scope": {"type":"string"}, "summary": {"type":"string"}},
-                //     "required": ["subject", "scope", "summary"]
-                // },
-            }))
+            .json(&body)
             .send()
             .expect("Error sending request");
 
@@ -103,9 +98,12 @@ impl OpenAICompatible {
             if response_json.choices.is_empty() {
                 panic!("No choices returned from OpenAI API");
             }
-            let choice = &response_json.choices[0];
             Ok(LLMResult {
-                commit_message: choice.message.content.clone().trim().to_string(),
+                choices: response_json
+                    .choices
+                    .iter()
+                    .map(|choice| choice.message.content.trim().to_string())
+                    .collect(),
                 total_tokens: response_json.usage.total_tokens,
                 prompt_tokens: response_json.usage.prompt_tokens,
                 completion_tokens: response_json.usage.completion_tokens,
@@ -28,11 +28,10 @@ impl OpenAICompatibleBuilder {
         }
     }
 
-    pub fn build(self, prompt: String) -> OpenAICompatible {
+    pub fn build(self) -> OpenAICompatible {
         OpenAICompatible {
             url: self.url,
             model: self.model,
-            prompt: prompt,
             api_key: self.api_key,
         }
     }
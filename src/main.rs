@@ -1,11 +1,28 @@
 use crate::llm::PromptModel;
 use clap::{Parser, Subcommand};
-use prompt::Prompt;
 
 mod ai;
+mod amend;
+mod branch;
+mod bump;
+mod changelog;
+mod commit;
 mod config;
+mod explain;
+mod fixup;
+mod hook;
+mod lint;
 mod llm;
+mod pr;
 mod prompt;
+mod report;
+mod review;
+mod reword;
+mod scope;
+mod split;
+mod squash;
+mod stash;
+mod style;
 
 #[derive(Parser)]
 #[command(
@@ -24,8 +41,17 @@ struct Cli {
     #[arg(short, long)]
     model: Option<String>,
 
-    #[arg(short='p', long, default_value_t=Prompt::P1)]
-    prompt: Prompt,
+    /// name of the prompt template, see `gitbuddy prompt list` [default: the style's prompt or p1]
+    #[arg(short = 'p', long)]
+    prompt: Option<String>,
+
+    /// commit message style, overrides the `style` of the repository config
+    #[arg(long, value_enum)]
+    style: Option<style::preset::Preset>,
+
+    /// language of the commit message, e.g. `en` or `zh`, overrides the repository and global config
+    #[arg(long)]
+    lang: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -38,6 +64,19 @@ enum Commands {
         /// test argument, generate commit message but not commit
         #[arg(long, default_value_t = false)]
         dry_run: bool,
+        /// use the last N commit messages of the repository as style examples
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        history: usize,
+        /// only sample commits touching the staged files
+        #[arg(long, default_value_t = false, requires = "history")]
+        history_paths: bool,
+        /// generate N candidate messages and pick one of them
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 1,
+              value_parser = clap::value_parser!(u8).range(1..=10))]
+        candidates: u8,
+        /// open the generated message in the git editor and commit the result
+        #[arg(short, long, default_value_t = false)]
+        edit: bool,
         // #[arg(long, default_value_t=String::from("deepseek"))]
         // vendor: String,
     },
@@ -49,6 +88,203 @@ enum Commands {
         #[arg(long)]
         model: Option<String>,
     },
+    /// Manage prompt templates
+    Prompt {
+        #[command(subcommand)]
+        command: PromptCommands,
+    },
+    /// Inspect the commit conventions of the repository
+    Style {
+        #[command(subcommand)]
+        command: StyleCommands,
+    },
+    /// Check a commit message against the rules of the repository
+    Lint {
+        /// file with the commit message, e.g. `.git/COMMIT_EDITMSG`, reads stdin when omitted
+        file: Option<std::path::PathBuf>,
+        /// apply the safe fixes and write the message back
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+    },
+    /// Generate a pull request title and description for the current branch
+    Pr {
+        /// branch to compare against [default: `pr.base` of the repository config, else the default branch of origin]
+        #[arg(long)]
+        base: Option<String>,
+        /// write the title and description to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Review the staged changes, or a range of commits, for bugs and other problems
+    Review {
+        /// commits to review, e.g. `main..HEAD`, `main...feature` or `HEAD~3` [default: the staged changes]
+        range: Option<String>,
+        /// exit with an error when a finding is at least this severe, to use it as a pre-commit gate
+        #[arg(long, value_enum)]
+        fail_on: Option<review::Severity>,
+    },
+    /// Render a Keep-a-Changelog section from the Conventional Commits of a range
+    Changelog {
+        /// commits to include, e.g. `v1.2.0..HEAD`
+        range: String,
+        /// rewrite the changelog into release notes with the LLM
+        #[arg(long, default_value_t = false)]
+        polish: bool,
+    },
+    /// Tag the next semantic version, derived from the Conventional Commits since the last version tag
+    Bump {
+        /// also update the version of `Cargo.toml` and `package.json` and commit it before tagging
+        #[arg(long, default_value_t = false)]
+        write: bool,
+        /// show the next version and the tag message but change nothing
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Generate new messages for the commits of a range and rewrite them
+    Reword {
+        /// commits to reword, ending at HEAD, e.g. `main..HEAD` or `HEAD~5`
+        range: String,
+        /// also rewrite commits that are already on the upstream branch
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Regenerate the message of the last commit, amending the staged changes into it
+    Amend {
+        /// show the new message, but do not amend
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Generate one commit message for all commits of the branch, and squash them into it with --commit
+    Squash {
+        /// branch the current branch is compared against, e.g. `main`
+        base: String,
+        /// soft reset to the merge base and commit the branch as a single commit
+        #[arg(long, default_value_t = false)]
+        commit: bool,
+        /// show the message, but do not reset or commit
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Split the staged changes into atomic commits planned by the model
+    Split {
+        /// only show the planned commits
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Commit staged hunks as `fixup!` of the unpushed commits that last changed their lines
+    Fixup {
+        /// where the stack of unpushed commits starts, the upstream branch by default
+        #[arg(long)]
+        base: Option<String>,
+        /// squash the fixup commits into their targets with `git rebase -i --autosquash`
+        #[arg(long, default_value_t = false)]
+        rebase: bool,
+        /// only show which commit each hunk belongs to
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Suggest a branch name for the described or uncommitted work and switch to it
+    Branch {
+        /// what the branch is for, the uncommitted changes are used when not given
+        description: Option<String>,
+        /// ticket for the `{ticket}` placeholder of the pattern, e.g. `PROJ-123`
+        #[arg(long)]
+        ticket: Option<String>,
+        /// only show the branch name
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Explain in plain language what a commit or a range of commits changed, why and where the risks are
+    Explain {
+        /// a commit, or a range like `main..HEAD`
+        target: String,
+    },
+    /// Write an activity report of the commits of a period, for a standup or a weekly update
+    Report {
+        /// start of the period, anything `git log --since` understands
+        #[arg(long, default_value = "1 week ago")]
+        since: String,
+        /// only commits of this author, `me` for the configured `user.email`
+        #[arg(long)]
+        author: Option<String>,
+        /// print the commits grouped by project and theme, without the LLM
+        #[arg(long, default_value_t = false)]
+        raw: bool,
+    },
+    /// Stash the local changes with a generated description instead of "WIP on <branch>"
+    Stash {
+        /// also stash untracked files
+        #[arg(short = 'u', long, default_value_t = false)]
+        include_untracked: bool,
+        /// only show the description
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// only stash the changes under these pathspecs
+        #[arg(last = true)]
+        paths: Vec<String>,
+    },
+    /// Manage the git hooks that run gitbuddy from a plain `git commit`
+    Hook {
+        #[command(subcommand)]
+        command: HookCommands,
+    },
+    /// Sequence editor of the rebases gitbuddy runs
+    #[command(hide = true)]
+    SequenceEditor {
+        /// the todo list git asks to edit
+        file: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum PromptCommands {
+    /// List the builtin prompts and the templates of the global and repository prompt dirs
+    List,
+    /// Print a prompt template
+    Show { name: String },
+}
+
+#[derive(Subcommand)]
+enum StyleCommands {
+    /// Print the conventions detected from the recent commit history
+    Analyze {
+        /// number of recent commits to analyze
+        #[arg(short = 'n', long, default_value_t = 100)]
+        count: usize,
+        /// only analyze commits touching these paths
+        paths: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HookCommands {
+    /// Install a hook, an existing hook is kept and run first
+    Install {
+        #[arg(value_enum, default_value_t = hook::Hook::PrepareCommitMsg)]
+        hook: hook::Hook,
+        /// let the commit-msg hook apply the safe fixes instead of only rejecting the message
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+    },
+    /// Remove a hook and restore the one it replaced
+    Uninstall {
+        #[arg(value_enum, default_value_t = hook::Hook::PrepareCommitMsg)]
+        hook: hook::Hook,
+    },
+    /// Show which hooks are installed
+    Status,
+    /// Entry point of the installed hooks
+    #[command(hide = true)]
+    Run {
+        #[arg(value_enum)]
+        hook: hook::Hook,
+        /// apply the safe fixes in the commit-msg hook
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+        /// the arguments git passes to the hook
+        #[arg(allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
 }
 
 fn main() {
@@ -58,9 +294,25 @@ fn main() {
         Some(Commands::Ai {
             push,
             dry_run,
+            history,
+            history_paths,
+            candidates,
+            edit,
             // vendor,
         }) => {
-            ai::handler(*push, *dry_run, cli.vendor, cli.model, cli.prompt);
+            ai::handler(ai::Options {
+                push: *push,
+                dry_run: *dry_run,
+                vendor: cli.vendor,
+                model: cli.model,
+                prompt: cli.prompt.clone(),
+                style: cli.style,
+                language: cli.lang.clone(),
+                history: *history,
+                history_paths: *history_paths,
+                candidates: *candidates as usize,
+                edit: *edit,
+            });
         }
         Some(Commands::Config { vendor, api_key, model }) => {
             let model = if let Some(model) = model {
@@ -71,6 +323,150 @@ fn main() {
 
             config::handler(vendor, api_key, model.as_str()).unwrap();
         }
-        None => ai::handler(false, false, cli.vendor, cli.model, cli.prompt),
+        Some(Commands::Prompt { command }) => match command {
+            PromptCommands::List => exit_on_error(prompt::list_handler()),
+            PromptCommands::Show { name } => exit_on_error(prompt::show_handler(name)),
+        },
+        Some(Commands::Style { command }) => match command {
+            StyleCommands::Analyze { count, paths } => exit_on_error(style::analyze_handler(*count, paths)),
+        },
+        Some(Commands::Lint { file, fix }) => {
+            exit_on_error(lint::handler(file.as_deref(), *fix, false, cli.style, cli.lang.clone()))
+        }
+        Some(Commands::Pr { base, output }) => exit_on_error(pr::handler(pr::Options {
+            base: base.clone(),
+            output: output.clone(),
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            language: cli.lang.clone(),
+        })),
+        Some(Commands::Review { range, fail_on }) => exit_on_error(review::handler(review::Options {
+            range: range.clone(),
+            fail_on: *fail_on,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            language: cli.lang.clone(),
+        })),
+        Some(Commands::Changelog { range, polish }) => exit_on_error(changelog::handler(changelog::Options {
+            range: range.clone(),
+            polish: *polish,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            language: cli.lang.clone(),
+        })),
+        Some(Commands::Bump { write, dry_run }) => exit_on_error(bump::handler(bump::Options {
+            write: *write,
+            dry_run: *dry_run,
+        })),
+        Some(Commands::Reword { range, force }) => exit_on_error(reword::handler(
+            range,
+            *force,
+            ai::Options {
+                vendor: cli.vendor,
+                model: cli.model.clone(),
+                prompt: cli.prompt.clone(),
+                style: cli.style,
+                language: cli.lang.clone(),
+                ..Default::default()
+            },
+        )),
+        Some(Commands::Amend { dry_run }) => exit_on_error(amend::handler(ai::Options {
+            dry_run: *dry_run,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            prompt: cli.prompt.clone(),
+            style: cli.style,
+            language: cli.lang.clone(),
+            ..Default::default()
+        })),
+        Some(Commands::Squash { base, commit, dry_run }) => exit_on_error(squash::handler(
+            base,
+            *commit,
+            ai::Options {
+                dry_run: *dry_run,
+                vendor: cli.vendor,
+                model: cli.model.clone(),
+                prompt: cli.prompt.clone(),
+                style: cli.style,
+                language: cli.lang.clone(),
+                ..Default::default()
+            },
+        )),
+        Some(Commands::Split { dry_run }) => exit_on_error(split::handler(ai::Options {
+            dry_run: *dry_run,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            style: cli.style,
+            language: cli.lang.clone(),
+            ..Default::default()
+        })),
+        Some(Commands::Fixup { base, rebase, dry_run }) => exit_on_error(fixup::handler(fixup::Options {
+            base: base.clone(),
+            rebase: *rebase,
+            dry_run: *dry_run,
+        })),
+        Some(Commands::Branch {
+            description,
+            ticket,
+            dry_run,
+        }) => exit_on_error(branch::handler(branch::Options {
+            description: description.clone(),
+            ticket: ticket.clone(),
+            dry_run: *dry_run,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+        })),
+        Some(Commands::Explain { target }) => exit_on_error(explain::handler(explain::Options {
+            target: target.clone(),
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            language: cli.lang.clone(),
+        })),
+        Some(Commands::Report { since, author, raw }) => exit_on_error(report::handler(report::Options {
+            since: since.clone(),
+            author: author.clone(),
+            raw: *raw,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+            language: cli.lang.clone(),
+        })),
+        Some(Commands::Stash {
+            include_untracked,
+            dry_run,
+            paths,
+        }) => exit_on_error(stash::handler(stash::Options {
+            include_untracked: *include_untracked,
+            paths: paths.clone(),
+            dry_run: *dry_run,
+            vendor: cli.vendor,
+            model: cli.model.clone(),
+        })),
+        Some(Commands::Hook { command }) => match command {
+            HookCommands::Install { hook, fix } => exit_on_error(hook::install_handler(*hook, *fix)),
+            HookCommands::Uninstall { hook } => exit_on_error(hook::uninstall_handler(*hook)),
+            HookCommands::Status => exit_on_error(hook::status_handler()),
+            HookCommands::Run { hook, fix, args } => exit_on_error(hook::run_handler(*hook, *fix, args)),
+        },
+        Some(Commands::SequenceEditor { file }) => exit_on_error(reword::sequence_editor_handler(file)),
+        None => ai::handler(ai::Options {
+            push: false,
+            dry_run: false,
+            vendor: cli.vendor,
+            model: cli.model,
+            prompt: cli.prompt,
+            style: cli.style,
+            language: cli.lang,
+            history: 0,
+            history_paths: false,
+            candidates: 1,
+            edit: false,
+        }),
+    }
+}
+
+fn exit_on_error(result: anyhow::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }
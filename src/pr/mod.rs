@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+use crate::ai;
+use crate::ai::git::{
+    change_summary, git_current_branch, git_default_branch, git_log_oneline, git_merge_base, git_range_changes,
+    git_range_diff,
+};
+use crate::commit::language;
+use crate::config::repo::RepoConfig;
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+const PR_PROMPT: &str = r#"You are an experienced software engineer writing a pull request for the changes of a branch.
+You get the list of commits of the branch and the aggregated diff against the base branch.
+
+Write the pull request in {language}:
+- The first line is the title: at most 72 characters, imperative mood, no trailing period, no Markdown.
+- Then an empty line, followed by the description in GitHub flavored Markdown with these sections:
+  ## Summary
+  What the pull request does and why, in 2-4 sentences.
+  ## Changes
+  A bullet list of the notable changes, grouped by area when there are many.
+  ## Testing
+  How the changes can be verified, based on the tests and code in the diff. Do not claim tests were run.
+  ## Breaking changes
+  Changes that require action from users or other code, or "None".
+
+Only describe what the commits and the diff show. Output ONLY the title and the description."#;
+
+/// Options of `gitbuddy pr`
+pub struct Options {
+    /// branch to compare against
+    pub base: Option<String>,
+    /// file to write the title and description to instead of stdout
+    pub output: Option<String>,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Generate a pull request title and description for the current branch
+pub fn handler(options: Options) -> Result<()> {
+    let repo_config = RepoConfig::load()?;
+    let base = options
+        .base
+        .clone()
+        .or(repo_config.pr.base.clone())
+        .or_else(git_default_branch)
+        .ok_or_else(|| anyhow!("Can not find the default branch of `origin`, choose the base branch with --base"))?;
+
+    let merge_base = git_merge_base(&base, "HEAD").ok_or_else(|| anyhow!("No common ancestor with `{}`", base))?;
+    let commits = git_log_oneline(&merge_base, "HEAD");
+    if commits.is_empty() {
+        return Err(anyhow!("No commits between `{}` and HEAD", base));
+    }
+
+    let diff = format!(
+        "{}\n{}",
+        change_summary(&git_range_changes(&merge_base, "HEAD")),
+        truncate_diff(&git_range_diff(&merge_base, "HEAD"), DEFAULT_DIFF_TOKENS)
+    );
+    let language = ai::configured_language(options.language.clone(), &repo_config);
+    let system_prompt = PR_PROMPT.replace(
+        "{language}",
+        &language::normalize(language.as_deref().unwrap_or("English")),
+    );
+    let user_message = format!(
+        "Branch: {}\nBase branch: {}\n\nCommits:\n{}\n\nDiff:\n{}",
+        git_current_branch(),
+        base,
+        commits.join("\n"),
+        diff
+    );
+
+    let result = llm::request_with_progress(
+        &format!("Generating pull request for {} commits", commits.len()),
+        &[ChatMessage::system(&system_prompt), ChatMessage::user(&user_message)],
+        options.vendor,
+        options.model.clone(),
+    )?;
+
+    let (title, description) = split_title(result.message());
+    let text = format!("{}\n\n{}\n", title, description);
+    match &options.output {
+        Some(path) => {
+            fs::write(Path::new(path), text)?;
+            eprintln!("Written to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Splits the answer into the title and the description, dropping Markdown the model may put on the title
+fn split_title(text: &str) -> (String, String) {
+    let text = text.trim();
+    let (title, description) = text.split_once('\n').unwrap_or((text, ""));
+
+    let title = title.trim().trim_start_matches('#').trim().trim_matches('*');
+    let title = title.strip_prefix("Title:").unwrap_or(title);
+    let title = title.trim().trim_matches('*').trim_matches('`').trim();
+    (title.to_string(), description.trim().to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_title() {
+        let (title, description) = split_title("# Add login endpoint\n\n## Summary\nAdds login.");
+        assert_eq!(title, "Add login endpoint");
+        assert_eq!(description, "## Summary\nAdds login.");
+
+        let (title, description) = split_title("**Title: Fix crash**");
+        assert_eq!(title, "Fix crash");
+        assert_eq!(description, "");
+    }
+}
@@ -0,0 +1,114 @@
+/// Rough number of characters per token for code and English text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens of diff sent along with a prompt, leaves room for the prompt and the answer in common context windows
+pub const DEFAULT_DIFF_TOKENS: usize = 12_000;
+
+/// Estimates the number of tokens of a text without a tokenizer
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Shrinks a unified diff to about `max_tokens`. Every file keeps its header, the budget is shared so that
+/// small files stay complete and only the largest ones are cut.
+pub fn truncate_diff(diff: &str, max_tokens: usize) -> String {
+    if estimate_tokens(diff) <= max_tokens {
+        return diff.to_string();
+    }
+
+    let sections = split_files(diff);
+
+    // hand out the budget from the smallest file up, whatever a small file does not use goes to the larger ones
+    let mut order: Vec<usize> = (0..sections.len()).collect();
+    order.sort_by_key(|i| sections[*i].len());
+    let mut allowed = vec![0; sections.len()];
+    let mut remaining = max_tokens * CHARS_PER_TOKEN;
+    for (n, i) in order.iter().enumerate() {
+        let share = remaining / (sections.len() - n);
+        allowed[*i] = sections[*i].len().min(share);
+        remaining -= allowed[*i];
+    }
+
+    let mut result = String::new();
+    for (section, allowed) in sections.iter().zip(allowed) {
+        if section.len() <= allowed {
+            result.push_str(section);
+            continue;
+        }
+
+        let mut used = 0;
+        let mut omitted = 0;
+        // the file header says which file it is, keep it up to the first hunk even when the budget is exhausted
+        let mut is_header = section.starts_with("diff --git ");
+        for line in section.split_inclusive('\n') {
+            if line.starts_with("@@") {
+                is_header = false;
+            }
+            if is_header || (omitted == 0 && used + line.len() <= allowed) {
+                result.push_str(line);
+                used += line.len();
+            } else {
+                omitted += 1;
+            }
+        }
+        result.push_str(&format!("[... {} more lines of this file omitted]\n", omitted));
+    }
+    result
+}
+
+/// Splits a diff at its `diff --git` lines, text before the first file is a section of its own
+fn split_files(diff: &str) -> Vec<&str> {
+    let mut starts = vec![0];
+    starts.extend(diff.match_indices("\ndiff --git ").map(|(i, _)| i + 1));
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, start)| &diff[*start..starts.get(n + 1).copied().unwrap_or(diff.len())])
+        .filter(|section| !section.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file_diff(name: &str, lines: usize) -> String {
+        let mut diff = format!(
+            "diff --git a/{name} b/{name}\nindex 1..2 100644\n--- a/{name}\n+++ b/{name}\n@@ -1 +1,{lines} @@\n"
+        );
+        for n in 0..lines {
+            diff.push_str(&format!("+line {} of {}\n", n, name));
+        }
+        diff
+    }
+
+    #[test]
+    fn test_truncate_diff() {
+        let small = file_diff("small.rs", 3);
+        let large = file_diff("large.rs", 2000);
+        let diff = format!("{}{}", small, large);
+
+        assert_eq!(truncate_diff(&small, 1000), small);
+
+        let truncated = truncate_diff(&diff, 500);
+        assert!(estimate_tokens(&truncated) < 550);
+        assert!(truncated.starts_with(&small));
+        assert!(truncated.contains("+++ b/large.rs"));
+        assert!(truncated.contains("more lines of this file omitted"));
+    }
+
+    #[test]
+    fn test_truncate_diff_keeps_long_headers() {
+        let mut diff = "diff --git a/new.rs b/new.rs\nnew file mode 100644\nindex 0000000..1234567\n\
+                        --- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,2000 @@\n"
+            .to_string();
+        for n in 0..2000 {
+            diff.push_str(&format!("+line {}\n", n));
+        }
+
+        let truncated = truncate_diff(&diff, 10);
+        assert!(truncated.contains("--- /dev/null\n+++ b/new.rs\n"));
+        assert!(truncated.contains("more lines of this file omitted"));
+    }
+}
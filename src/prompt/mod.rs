@@ -1,6 +1,12 @@
+use crate::commit::rules::Rules;
+use anyhow::Result;
 use clap::ValueEnum;
+use colored::Colorize;
 use std::fmt::{Display, Formatter};
 
+pub mod budget;
+pub mod template;
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum Prompt {
     P1,
@@ -34,6 +40,111 @@ impl Prompt {
     }
 }
 
+/// Everything a prompt template can refer to, plus the repository context appended to it
+#[derive(Debug, Default)]
+pub struct PromptContext {
+    /// Staged change summary followed by the diff
+    pub diff: String,
+    /// Staged file names
+    pub files: Vec<String>,
+    /// Current branch name
+    pub branch: String,
+    /// Language of the commit message
+    pub language: String,
+    /// Recent commit messages used as few-shot style examples
+    pub examples: Vec<String>,
+    /// Constraints the generated message has to satisfy
+    pub rules: Rules,
+}
+
+impl PromptContext {
+    /// Renders the template into the system prompt and the user message sent to the model
+    pub fn messages(&self, template: &str) -> Result<(String, String)> {
+        let mut prompt = template::render(template, &self.variables())?.trim_end().to_string();
+
+        if !self.examples.is_empty() && !template::uses(template, "recent_commits") {
+            prompt.push_str(
+                "\n\nFollow the conventions of this repository's recent commit messages \
+                 (types, scopes, ticket prefixes, language, capitalization). Examples:\n",
+            );
+            prompt.push_str(&self.recent_commits());
+        }
+
+        let instructions = self.rules.instructions();
+        if !instructions.is_empty() {
+            prompt.push_str("\n\nAdditional requirements:\n");
+            for instruction in instructions {
+                prompt.push_str("- ");
+                prompt.push_str(&instruction);
+                prompt.push('\n');
+            }
+        }
+
+        // templates embedding the diff themselves only need a short request as user message
+        let user_message = if template::uses(template, "diff") {
+            "Write the commit message for the changes above.".to_string()
+        } else {
+            format!("diff content: \n{}", self.diff)
+        };
+
+        Ok((prompt, user_message))
+    }
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        let scope_hint = match &self.rules.required_scope {
+            Some(scope) => scope.clone(),
+            None => self.rules.scopes.join(", "),
+        };
+
+        vec![
+            ("diff", self.diff.clone()),
+            ("files", self.files.join("\n")),
+            ("branch", self.branch.clone()),
+            ("recent_commits", self.recent_commits()),
+            ("language", self.language.clone()),
+            ("scope_hint", scope_hint),
+        ]
+    }
+
+    fn recent_commits(&self) -> String {
+        let mut text = String::new();
+        for example in &self.examples {
+            // long bodies add little about the style, keep the header and the first lines only
+            let example: Vec<&str> = example.lines().take(MAX_EXAMPLE_LINES).collect();
+            text.push_str("---\n");
+            text.push_str(&example.join("\n"));
+            text.push('\n');
+        }
+        if !text.is_empty() {
+            text.push_str("---\n");
+        }
+        text
+    }
+}
+
+/// Print the available prompt templates
+pub fn list_handler() -> Result<()> {
+    for template in template::list() {
+        let path = template.path.map(|p| p.display().to_string()).unwrap_or_default();
+        println!(
+            "{:<16} {:<8} {}",
+            template.name,
+            template.source.to_string(),
+            path.truecolor(128, 128, 128)
+        );
+    }
+    Ok(())
+}
+
+/// Print the content of a prompt template
+pub fn show_handler(name: &str) -> Result<()> {
+    let template = template::find(name)?;
+    println!("{}", template.content.trim_end());
+    Ok(())
+}
+
+const MAX_EXAMPLE_LINES: usize = 8;
+
 pub const PROMPT: &str = r###"You are a professional Git assistant. Based on the provided code changes, generate a concise Git Commit Message following the format:
 <type>(<optional scope>): <subject>
 
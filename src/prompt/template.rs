@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+
+use crate::config;
+use crate::config::repo::repo_dir;
+use crate::prompt::Prompt;
+
+const PROMPTS_DIR: &str = "prompts";
+
+/// Variables available in prompt templates as `{{name}}`
+pub const VARIABLES: [&str; 6] = ["diff", "files", "branch", "recent_commits", "language", "scope_hint"];
+
+/// Where a prompt template comes from, later sources override earlier ones with the same name
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Source {
+    Builtin,
+    Global,
+    Repo,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Builtin => write!(f, "builtin"),
+            Source::Global => write!(f, "global"),
+            Source::Repo => write!(f, "repo"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub source: Source,
+    pub path: Option<PathBuf>,
+    pub content: String,
+}
+
+/// All available templates, ordered by source and name
+pub fn list() -> Vec<Template> {
+    let mut templates: Vec<Template> = Prompt::value_variants()
+        .iter()
+        .map(|prompt| Template {
+            name: prompt.to_string(),
+            source: Source::Builtin,
+            path: None,
+            content: prompt.value().to_string(),
+        })
+        .collect();
+
+    if let Some(dir) = config::get_config_dir() {
+        templates.extend(load_dir(&dir.join(PROMPTS_DIR), Source::Global));
+    }
+    if let Some(dir) = repo_dir() {
+        templates.extend(load_dir(&dir.join(PROMPTS_DIR), Source::Repo));
+    }
+
+    templates
+}
+
+/// Find a template by name, preferring the repository over the global config dir over the builtin prompts
+pub fn find(name: &str) -> Result<Template> {
+    list()
+        .into_iter()
+        .rev()
+        .find(|template| template.name == name)
+        .ok_or_else(|| {
+            anyhow!(
+                "Prompt `{}` not found, run `gitbuddy prompt list` to see the available ones",
+                name
+            )
+        })
+}
+
+fn load_dir(dir: &Path, source: Source) -> Vec<Template> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut templates: Vec<Template> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let content = fs::read_to_string(&path).ok()?;
+            Some(Template {
+                name,
+                source,
+                path: Some(path),
+                content,
+            })
+        })
+        .collect();
+
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+/// Replaces every `{{name}}` in the template, failing on unknown variables so typos do not go unnoticed
+pub fn render(template: &str, variables: &[(&str, String)]) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        let name = rest[start + 2..end].trim();
+        let value = variables
+            .iter()
+            .find(|(variable, _)| *variable == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown variable `{{{{{}}}}}` in prompt template, available: {}",
+                    name,
+                    VARIABLES.join(", ")
+                )
+            })?;
+
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Whether the template refers to the variable
+pub fn uses(template: &str, name: &str) -> bool {
+    template
+        .split("{{")
+        .skip(1)
+        .filter_map(|part| part.split_once("}}"))
+        .any(|(variable, _)| variable.trim() == name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let variables = [
+            ("branch", "feat/login".to_string()),
+            ("files", "a.rs\nb.rs".to_string()),
+        ];
+
+        let rendered = render("Branch: {{branch}}\nFiles:\n{{ files }}\n", &variables).unwrap();
+        assert_eq!(rendered, "Branch: feat/login\nFiles:\na.rs\nb.rs\n");
+
+        assert!(render("{{ nope }}", &variables).is_err());
+        assert_eq!(render("plain {{ text", &variables).unwrap(), "plain {{ text");
+    }
+
+    #[test]
+    fn test_uses() {
+        assert!(uses("Diff:\n{{ diff }}", "diff"));
+        assert!(!uses("Diff:\n{{ files }}", "diff"));
+        assert!(!uses("no variables", "diff"));
+    }
+
+    #[test]
+    fn test_builtin_templates() {
+        let template = find("p3").unwrap();
+        assert_eq!(template.source, Source::Builtin);
+        assert!(find("does-not-exist").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+
+use crate::ai;
+use crate::ai::git::{git_log_activity, git_repo_config, git_toplevel};
+use crate::changelog::Entry;
+use crate::commit::language;
+use crate::config::repo::RepoConfig;
+use crate::config::GlobalConfig;
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+
+const REPORT_PROMPT: &str = r#"You write short activity reports for a standup or a weekly update. You get the commits
+of the period, grouped by project and theme.
+
+Write the report in {language} as GitHub flavored Markdown:
+- one `##` section per project, most active project first
+- a few bullets per project with the outcomes, merging commits that belong to the same piece of work
+- no commit hashes, no filler, nothing that is not in the commits
+- end with a one line `**Summary:**` across all projects
+
+Output ONLY the report."#;
+
+/// Themes of the report with the Conventional Commits types they collect, other commits go to "Other"
+const THEMES: [(&str, &[&str]); 7] = [
+    ("Features", &["feat"]),
+    ("Fixes", &["fix"]),
+    ("Performance", &["perf"]),
+    ("Refactoring", &["refactor", "style"]),
+    ("Documentation", &["docs"]),
+    ("Tests", &["test"]),
+    ("Build and maintenance", &["build", "ci", "chore", "revert"]),
+];
+
+/// A commit of the report
+struct Activity {
+    date: String,
+    entry: Entry,
+}
+
+/// Options of `gitbuddy report`
+pub struct Options {
+    /// start of the period, anything `git log --since` understands
+    pub since: String,
+    /// only commits of this author, `me` for `user.email` of each repository
+    pub author: Option<String>,
+    /// print the grouped commits without the LLM
+    pub raw: bool,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Report the commits of the current and the configured repositories for a period
+pub fn handler(options: Options) -> Result<()> {
+    let mut candidates: Vec<PathBuf> = git_toplevel().into_iter().collect();
+    if let Some(config) = GlobalConfig::load() {
+        candidates.extend(config.report.repos.iter().map(|repo| expand_home(repo)));
+    }
+    let mut repos: Vec<PathBuf> = vec![];
+    for repo in candidates {
+        let repo = repo.canonicalize().unwrap_or(repo);
+        if !repos.contains(&repo) {
+            repos.push(repo);
+        }
+    }
+    if repos.is_empty() {
+        return Err(anyhow!(
+            "Not in a git repository and no repositories in the `[report] repos` config"
+        ));
+    }
+
+    let mut projects = vec![];
+    for repo in &repos {
+        let author = match options.author.as_deref() {
+            Some("me") => Some(
+                git_repo_config(repo, "user.email")
+                    .ok_or_else(|| anyhow!("No user.email configured for {}", repo.display()))?,
+            ),
+            author => author.map(|author| author.to_string()),
+        };
+        let activities: Vec<Activity> = git_log_activity(repo, &options.since, author.as_deref())
+            .into_iter()
+            .map(|(hash, date, subject)| Activity {
+                date,
+                entry: Entry::parse(&hash, &subject),
+            })
+            .collect();
+        if !activities.is_empty() {
+            let name = repo
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(repo.display().to_string());
+            projects.push((name, activities));
+        }
+    }
+    if projects.is_empty() {
+        println!("No commits since {}", options.since);
+        return Ok(());
+    }
+
+    projects.sort_by_key(|(_, activities)| Reverse(activities.len()));
+    let raw = render(&projects);
+    if options.raw {
+        print!("{}", raw);
+        return Ok(());
+    }
+
+    let repo_config = RepoConfig::load()?;
+    let language = ai::configured_language(options.language.clone(), &repo_config);
+    let system_prompt = REPORT_PROMPT.replace(
+        "{language}",
+        &language::normalize(language.as_deref().unwrap_or("English")),
+    );
+    let commits: usize = projects.iter().map(|(_, activities)| activities.len()).sum();
+
+    let result = llm::request_with_progress(
+        &format!("Writing the report for {} commits", commits),
+        &[
+            ChatMessage::system(&system_prompt),
+            ChatMessage::user(&format!("Period: since {}\n\n{}", options.since, raw)),
+        ],
+        options.vendor,
+        options.model.clone(),
+    )?;
+    println!("{}", result.message().trim());
+    Ok(())
+}
+
+/// Renders the commits as Markdown, a section per project with the commits grouped by theme
+fn render(projects: &[(String, Vec<Activity>)]) -> String {
+    let mut markdown = String::new();
+    for (name, activities) in projects {
+        markdown.push_str(&format!("## {}\n", name));
+
+        let mut themes: Vec<(&str, Vec<&Activity>)> = THEMES.iter().map(|(theme, _)| (*theme, vec![])).collect();
+        themes.push(("Other", vec![]));
+        for activity in activities {
+            let n = activity
+                .entry
+                .kind
+                .as_deref()
+                .and_then(|kind| THEMES.iter().position(|(_, kinds)| kinds.contains(&kind)))
+                .unwrap_or(THEMES.len());
+            themes[n].1.push(activity);
+        }
+
+        for (theme, activities) in themes.iter().filter(|(_, activities)| !activities.is_empty()) {
+            markdown.push_str(&format!("\n### {}\n\n", theme));
+            for Activity { date, entry } in activities.iter().copied() {
+                match &entry.scope {
+                    Some(scope) => markdown.push_str(&format!(
+                        "- **{}:** {} ({}, {})\n",
+                        scope, entry.description, entry.hash, date
+                    )),
+                    None => markdown.push_str(&format!("- {} ({}, {})\n", entry.description, entry.hash, date)),
+                }
+            }
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let activity = |hash: &str, subject: &str| Activity {
+            date: "2026-10-12".to_string(),
+            entry: Entry::parse(hash, subject),
+        };
+        let projects = vec![
+            (
+                "api".to_string(),
+                vec![
+                    activity("a1", "fix(db): close connections"),
+                    activity("b2", "feat: add login"),
+                    activity("c3", "Update README"),
+                ],
+            ),
+            ("web".to_string(), vec![activity("d4", "ci: cache npm")]),
+        ];
+
+        assert_eq!(
+            render(&projects),
+            "## api
+
+### Features
+
+- add login (b2, 2026-10-12)
+
+### Fixes
+
+- **db:** close connections (a1, 2026-10-12)
+
+### Other
+
+- Update README (c3, 2026-10-12)
+
+## web
+
+### Build and maintenance
+
+- cache npm (d4, 2026-10-12)
+
+"
+        );
+    }
+}
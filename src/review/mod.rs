@@ -0,0 +1,251 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::ai;
+use crate::ai::git::{
+    change_summary, git_range_changes, git_range_diff, git_resolve_range, git_stage_changes, git_stage_diff,
+};
+use crate::commit::language;
+use crate::config::repo::RepoConfig;
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+const REVIEW_PROMPT: &str = r#"You are a meticulous senior engineer reviewing a change before it is committed.
+Look for bugs, security issues, performance problems and style issues that matter. Only report real problems
+in the changed lines, do not praise, do not repeat what the code does, and skip nitpicks a formatter would fix.
+
+Answer with JSON only, no Markdown fences, in exactly this shape:
+{"findings": [{"file": "src/main.rs", "start_line": 10, "end_line": 12, "severity": "high",
+  "category": "bug", "message": "what is wrong and why", "suggestion": "how to fix it"}]}
+
+- line numbers refer to the new version of the file, taken from the hunk headers of the diff
+- severity is one of: low, medium, high, critical
+- category is one of: bug, security, performance, style
+- write message and suggestion in {language}
+- answer {"findings": []} when there is nothing to report"#;
+
+/// How serious a finding is, ordered from least to most severe
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn colored(&self) -> ColoredString {
+        let label = format!("{:<8}", self.to_string().to_uppercase());
+        match self {
+            Severity::Low => label.normal(),
+            Severity::Medium => label.yellow(),
+            Severity::High => label.red(),
+            Severity::Critical => label.red().bold(),
+        }
+    }
+}
+
+// models answer `High` as often as `high`, one capital must not fail the whole review
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Severity::from_str(value.trim(), true).map_err(de::Error::custom)
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Low => write!(f, "low"),
+            Severity::Medium => write!(f, "medium"),
+            Severity::High => write!(f, "high"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Category {
+    Bug,
+    Security,
+    Performance,
+    Style,
+    Other,
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(match value.trim().to_lowercase().as_str() {
+            "bug" => Category::Bug,
+            "security" => Category::Security,
+            "performance" => Category::Performance,
+            "style" => Category::Style,
+            _ => Category::Other,
+        })
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::Bug => write!(f, "bug"),
+            Category::Security => write!(f, "security"),
+            Category::Performance => write!(f, "performance"),
+            Category::Style => write!(f, "style"),
+            Category::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// A single problem reported by the model
+#[derive(Debug, Deserialize)]
+pub struct Finding {
+    pub file: String,
+    pub start_line: Option<u32>,
+    pub end_line: Option<u32>,
+    pub severity: Severity,
+    pub category: Category,
+    pub message: String,
+    #[serde(default)]
+    pub suggestion: Option<String>,
+}
+
+impl Finding {
+    fn location(&self) -> String {
+        match (self.start_line, self.end_line) {
+            (Some(start), Some(end)) if end > start => format!("{}:{}-{}", self.file, start, end),
+            (Some(start), _) => format!("{}:{}", self.file, start),
+            _ => self.file.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Review {
+    findings: Vec<Finding>,
+}
+
+/// Options of `gitbuddy review`
+pub struct Options {
+    /// commits to review, e.g. `main..HEAD`, the staged changes when not given
+    pub range: Option<String>,
+    /// fail when a finding is at least this severe
+    pub fail_on: Option<Severity>,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Review the staged changes or a range of commits and print the findings
+pub fn handler(options: Options) -> Result<()> {
+    let diff = match &options.range {
+        Some(range) => {
+            let (from, to) = git_resolve_range(range)?;
+            format!(
+                "{}\n{}",
+                change_summary(&git_range_changes(&from, &to)),
+                truncate_diff(&git_range_diff(&from, &to), DEFAULT_DIFF_TOKENS)
+            )
+        }
+        None => format!(
+            "{}\n{}",
+            change_summary(&git_stage_changes()),
+            truncate_diff(&git_stage_diff(), DEFAULT_DIFF_TOKENS)
+        ),
+    };
+    if !diff.contains("diff --git") {
+        return Err(anyhow!(
+            "Nothing to review, stage changes or pass a range like `main..HEAD`"
+        ));
+    }
+
+    let repo_config = RepoConfig::load()?;
+    let language = ai::configured_language(options.language.clone(), &repo_config);
+    let system_prompt = REVIEW_PROMPT.replace(
+        "{language}",
+        &language::normalize(language.as_deref().unwrap_or("English")),
+    );
+
+    let result = llm::request_with_progress(
+        "Reviewing changes",
+        &[ChatMessage::system(&system_prompt), ChatMessage::user(&diff)],
+        options.vendor,
+        options.model.clone(),
+    )?;
+
+    let mut findings = parse_findings(result.message())?;
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.file.cmp(&b.file)));
+
+    if findings.is_empty() {
+        println!("{}", "No findings".green());
+    }
+    for finding in &findings {
+        println!(
+            "{} {:<12} {}",
+            finding.severity.colored(),
+            finding.category.to_string(),
+            finding.location().bold()
+        );
+        println!("    {}", finding.message);
+        if let Some(suggestion) = &finding.suggestion {
+            println!("    {} {}", "suggestion:".cyan(), suggestion);
+        }
+    }
+
+    if let Some(threshold) = options.fail_on {
+        let failing = findings.iter().filter(|f| f.severity >= threshold).count();
+        if failing > 0 {
+            return Err(anyhow!("{} finding(s) of severity {} or higher", failing, threshold));
+        }
+    }
+    Ok(())
+}
+
+/// Parses the JSON answer
+fn parse_findings(answer: &str) -> Result<Vec<Finding>> {
+    let json = llm::extract_json(answer)?;
+
+    let review: Review =
+        serde_json::from_str(json).map_err(|e| anyhow!("Can not parse the review: {}\n{}", e, answer))?;
+    Ok(review.findings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_findings() {
+        let answer = r#"```json
+{"findings": [
+  {"file": "src/db.rs", "start_line": 10, "end_line": 14, "severity": "critical", "category": "security",
+   "message": "SQL built from user input", "suggestion": "use a bound parameter"},
+  {"file": "src/main.rs", "start_line": 3, "severity": "low", "category": "naming", "message": "unclear name"}
+]}
+```"#;
+        let findings = parse_findings(answer).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].location(), "src/db.rs:10-14");
+        assert_eq!(findings[0].category, Category::Security);
+        assert_eq!(findings[1].category, Category::Other);
+        assert_eq!(findings[1].location(), "src/main.rs:3");
+        assert!(findings[0].severity >= Severity::High);
+
+        assert!(parse_findings(r#"{"findings": []}"#).unwrap().is_empty());
+        assert!(parse_findings("Looks good to me").is_err());
+
+        let answer = r#"{"findings": [{"file": "a.rs", "start_line": 1, "severity": "High", "category": "Bug",
+                         "message": "off by one"}]}"#;
+        let findings = parse_findings(answer).unwrap();
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].category, Category::Bug);
+        let answer = answer.replace("High", "urgent");
+        assert!(parse_findings(&answer).is_err());
+    }
+}
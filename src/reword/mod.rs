@@ -0,0 +1,251 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai;
+use crate::ai::editor;
+use crate::ai::git::{
+    change_summary, git_commit_changes, git_commit_diff, git_dir, git_has_merges, git_is_ancestor, git_log_commits,
+    git_rebase_with_todo, git_remote_branches_containing, git_resolve_range, git_rev_parse, git_upstream,
+    REBASE_TODO_ENV,
+};
+use crate::ai::menu::ask;
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+const REWORD_DIR: &str = "gitbuddy-reword";
+/// Width of each column of the old/new comparison
+const COLUMN_WIDTH: usize = 48;
+
+/// What the user decided for one commit
+enum Decision {
+    Keep,
+    Reword(String),
+}
+
+/// Generate new messages for the commits of a range and rewrite them with a rebase
+pub fn handler(range: &str, force: bool, options: ai::Options) -> Result<()> {
+    let (from, to) = git_resolve_range(range)?;
+    let head = git_rev_parse("HEAD").ok_or_else(|| anyhow!("HEAD does not point to a commit"))?;
+    if git_rev_parse(&to).as_ref() != Some(&head) {
+        return Err(anyhow!(
+            "Only commits up to HEAD can be reworded, `{}` does not end at HEAD",
+            range
+        ));
+    }
+    if !git_is_ancestor(&from, "HEAD") {
+        return Err(anyhow!("`{}` is not an ancestor of HEAD", from));
+    }
+    if git_has_merges(&from, "HEAD") {
+        return Err(anyhow!(
+            "`{}` contains merge commits, rewording them would flatten the history",
+            range
+        ));
+    }
+
+    let commits = git_log_commits(Some(&from), "HEAD");
+    if commits.is_empty() {
+        return Err(anyhow!("No commits in `{}`", range));
+    }
+
+    // without an upstream, any remote branch containing a commit means it is published
+    let (published, remotes) = match git_upstream() {
+        Some(upstream) => (
+            commits
+                .iter()
+                .filter(|(hash, _)| git_is_ancestor(hash, &upstream))
+                .count(),
+            vec![upstream],
+        ),
+        None => {
+            let mut remotes: Vec<String> = vec![];
+            let mut published = 0;
+            for (hash, _) in &commits {
+                let branches = git_remote_branches_containing(hash);
+                if !branches.is_empty() {
+                    published += 1;
+                }
+                for branch in branches {
+                    if !remotes.contains(&branch) {
+                        remotes.push(branch);
+                    }
+                }
+            }
+            (published, remotes)
+        }
+    };
+    if published > 0 && !force {
+        return Err(anyhow!(
+            "{} of the commits are already on `{}`, rewriting them breaks the history of others. Use --force to do it anyway",
+            published,
+            remotes.join("`, `")
+        ));
+    }
+
+    let mut decisions = vec![];
+    for (n, (hash, message)) in commits.iter().enumerate() {
+        println!(
+            "\n{} {}",
+            format!("[{}/{}]", n + 1, commits.len()).bold(),
+            hash.yellow()
+        );
+        match decide(hash, message, &options)? {
+            Some(decision) => decisions.push((hash.clone(), decision)),
+            None => {
+                println!("{}", "Reword aborted, nothing was changed".red());
+                return Ok(());
+            }
+        }
+    }
+
+    let reworded = decisions
+        .iter()
+        .filter(|(_, d)| matches!(d, Decision::Reword(_)))
+        .count();
+    if reworded == 0 {
+        println!("Nothing to reword");
+        return Ok(());
+    }
+
+    let dir = git_dir().ok_or_else(|| anyhow!("Not git directory"))?.join(REWORD_DIR);
+    fs::create_dir_all(&dir)?;
+    let mut todo = String::new();
+    for (hash, decision) in &decisions {
+        todo.push_str(&format!("pick {}\n", hash));
+        if let Decision::Reword(message) = decision {
+            let path = dir.join(hash);
+            fs::write(&path, message)?;
+            let path = path.canonicalize()?.to_string_lossy().replace('\'', r"'\''");
+            todo.push_str(&format!(
+                "exec git commit --amend --allow-empty --cleanup=verbatim -F '{}'\n",
+                path
+            ));
+        }
+    }
+    let todo_path = dir.join("todo");
+    fs::write(&todo_path, todo)?;
+
+    // the pending `exec` lines of a stopped rebase still read the messages, so they stay until it succeeds
+    if let Err(e) = git_rebase_with_todo(&from, &todo_path) {
+        return Err(anyhow!(
+            "{}\nThe new messages are kept in {}. Resolve the conflicts and run `git rebase --continue` to \
+             finish rewording, or `git rebase --abort` to give up, then delete the directory.",
+            e,
+            dir.display()
+        ));
+    }
+    let _ = fs::remove_dir_all(&dir);
+
+    println!(
+        "{} {} of {} commits",
+        "Reworded".green().bold(),
+        reworded,
+        commits.len()
+    );
+    Ok(())
+}
+
+/// Entry point of `GIT_SEQUENCE_EDITOR` for the rebases gitbuddy runs, replaces the todo list git wrote
+/// with the one prepared in `GITBUDDY_REBASE_TODO`
+pub fn sequence_editor_handler(file: &Path) -> Result<()> {
+    let todo = std::env::var_os(REBASE_TODO_ENV).ok_or_else(|| {
+        anyhow!(
+            "{} is not set, this command is run by the rebase of `gitbuddy reword`",
+            REBASE_TODO_ENV
+        )
+    })?;
+    fs::copy(todo, file)?;
+    Ok(())
+}
+
+/// Generates a message for the commit and asks the user whether to use it, `None` to abort everything
+fn decide(hash: &str, old: &str, options: &ai::Options) -> Result<Option<Decision>> {
+    let changes = git_commit_changes(hash);
+    let filenames: Vec<String> = changes.iter().map(|change| change.path.clone()).collect();
+    let diff = format!(
+        "{}\n{}",
+        change_summary(&changes),
+        truncate_diff(&git_commit_diff(hash), DEFAULT_DIFF_TOKENS)
+    );
+
+    let mut new = ai::generate_message_for(options, filenames.clone(), diff.clone())?;
+    loop {
+        print!("{}", side_by_side(old, &new, COLUMN_WIDTH));
+        let answer = ask(&format!(
+            "Use the new message? {}es  {}o, keep the old one  {}dit  {}egenerate  {}uit (Y) ",
+            "[y]".bold(),
+            "[n]".bold(),
+            "[e]".bold(),
+            "[r]".bold(),
+            "[q]".bold()
+        ));
+
+        match answer.as_deref().map(|a| a.to_lowercase()).as_deref() {
+            Some("" | "y") => return Ok(Some(Decision::Reword(new))),
+            Some("n") => return Ok(Some(Decision::Keep)),
+            Some("e") => match editor::edit(&new, &filenames) {
+                Ok(edited) if !edited.is_empty() => new = edited,
+                Ok(_) => println!("Empty message, keeping the generated one"),
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("r") => match ai::generate_message_for(options, filenames.clone(), diff.clone()) {
+                Ok(message) => new = message,
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("q") | None => return Ok(None),
+            Some(other) => println!("Unknown option `{}`", other),
+        }
+    }
+}
+
+/// Renders two texts next to each other, wrapping lines longer than `width`
+fn side_by_side(left: &str, right: &str, width: usize) -> String {
+    let left = wrap_lines(left, width);
+    let right = wrap_lines(right, width);
+
+    let mut text = format!(
+        "{:<width$} │ new\n{}─┼─{}\n",
+        "old",
+        "─".repeat(width),
+        "─".repeat(width),
+        width = width
+    );
+    for n in 0..left.len().max(right.len()) {
+        let l = left.get(n).map(|s| s.as_str()).unwrap_or_default();
+        let r = right.get(n).map(|s| s.as_str()).unwrap_or_default();
+        let padding = width.saturating_sub(l.chars().count());
+        text.push_str(&format!("{}{} │ {}\n", l, " ".repeat(padding), r));
+    }
+    text
+}
+
+fn wrap_lines(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for line in text.trim().lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push(String::new());
+        }
+        for chunk in chars.chunks(width) {
+            lines.push(chunk.iter().collect());
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_side_by_side() {
+        let text = side_by_side("wip", "feat(api): add the login endpoint\n\nUses sessions.", 10);
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "old        │ new");
+        assert_eq!(lines[2], "wip        │ feat(api):");
+        assert_eq!(lines[3], "           │  add the l");
+        assert_eq!(lines.len(), 9);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::repo::ScopeConfig;
+
+/// Infers the Conventional Commits scopes of the staged files, in order of first appearance.
+/// Explicit mappings take precedence over workspace detection; files matching neither are ignored.
+pub fn infer_scopes(filenames: &[String], config: &ScopeConfig, root: &Path) -> Vec<String> {
+    let members = if config.auto { workspace_members(root) } else { vec![] };
+
+    let mut scopes: Vec<String> = vec![];
+    for filename in filenames {
+        let scope = config
+            .mapping
+            .iter()
+            .find(|m| glob_match(&m.path, filename))
+            .map(|m| m.scope.clone())
+            .or_else(|| member_scope(&members, filename));
+
+        if let Some(scope) = scope {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+    }
+    scopes
+}
+
+/// Scope of a file inside a workspace member: the name of the deepest member directory containing it
+fn member_scope(members: &[String], filename: &str) -> Option<String> {
+    let components: Vec<&str> = filename.split('/').collect();
+
+    (1..components.len()).rev().find_map(|depth| {
+        let dir = components[..depth].join("/");
+        members
+            .iter()
+            .any(|member| glob_match(member.trim_end_matches('/'), &dir))
+            .then(|| components[depth - 1].to_string())
+    })
+}
+
+/// Member globs of a Cargo workspace and of npm/yarn workspaces in the repository root
+fn workspace_members(root: &Path) -> Vec<String> {
+    let mut members = vec![];
+
+    if let Ok(content) = fs::read_to_string(root.join("Cargo.toml")) {
+        if let Ok(manifest) = content.parse::<toml::Table>() {
+            let cargo_members = manifest
+                .get("workspace")
+                .and_then(|w| w.get("members"))
+                .and_then(|m| m.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str().map(|s| s.to_string()));
+            members.extend(cargo_members);
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(root.join("package.json")) {
+        if let Ok(package) = serde_json::from_str::<serde_json::Value>(&content) {
+            // `workspaces` is either a list of globs or an object with a `packages` list
+            let workspaces = &package["workspaces"];
+            let globs = workspaces.as_array().or_else(|| workspaces["packages"].as_array());
+            members.extend(
+                globs
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|m| m.as_str().map(|s| s.trim_start_matches("./").to_string())),
+            );
+        }
+    }
+
+    members
+}
+
+/// Matches a `/` separated path against a glob supporting `*`, `?` and `**`
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => (0..=path.len()).any(|skip| match_segments(&pattern[1..], &path[skip..])),
+        Some(segment) => {
+            !path.is_empty()
+                && match_segment(segment.as_bytes(), path[0].as_bytes())
+                && match_segments(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some(b'*') => (0..=name.len()).any(|skip| match_segment(&pattern[1..], &name[skip..])),
+        Some(b'?') => !name.is_empty() && match_segment(&pattern[1..], &name[1..]),
+        Some(c) => name.first() == Some(c) && match_segment(&pattern[1..], &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::repo::ScopeMapping;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("crates/*", "crates/foo"));
+        assert!(!glob_match("crates/*", "crates/foo/src"));
+        assert!(glob_match("crates/**", "crates/foo/src/lib.rs"));
+        assert!(glob_match("**/*.md", "README.md"));
+        assert!(glob_match("packages/web-?", "packages/web-1"));
+        assert!(!glob_match("packages/web", "packages/website"));
+    }
+
+    #[test]
+    fn test_infer_scopes() {
+        let config = ScopeConfig {
+            auto: false,
+            mapping: vec![ScopeMapping {
+                path: "docs/**".to_string(),
+                scope: "docs".to_string(),
+            }],
+        };
+        let members = vec!["crates/*".to_string(), "tools/cli".to_string()];
+
+        assert_eq!(member_scope(&members, "crates/foo/src/lib.rs"), Some("foo".to_string()));
+        assert_eq!(member_scope(&members, "tools/cli/main.rs"), Some("cli".to_string()));
+        assert_eq!(member_scope(&members, "README.md"), None);
+
+        let filenames = vec![
+            "docs/a.md".to_string(),
+            "src/main.rs".to_string(),
+            "docs/b.md".to_string(),
+        ];
+        assert_eq!(
+            infer_scopes(&filenames, &config, Path::new(".")),
+            vec!["docs".to_string()]
+        );
+    }
+}
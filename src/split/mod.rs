@@ -0,0 +1,262 @@
+pub(crate) mod patch;
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::ai;
+use crate::ai::git::{
+    git_apply_cached, git_commit, git_read_tree, git_reset_soft, git_rev_parse, git_stage_patch, git_write_tree,
+};
+use crate::ai::menu::ask;
+use crate::commit::language;
+use crate::config::repo::RepoConfig;
+use crate::lint;
+use crate::llm;
+use crate::llm::ChatMessage;
+use crate::prompt::budget::{estimate_tokens, DEFAULT_DIFF_TOKENS};
+use patch::{FilePatch, Hunk};
+
+const SPLIT_PROMPT: &str = r#"You are an experienced software engineer preparing a clean history. The staged changes
+mix unrelated work. Group the numbered hunks into atomic commits: each commit does one logical thing, builds on
+the previous ones and gets its own commit message. Keep hunks that depend on each other in the same commit.
+
+Answer with JSON only, no Markdown fences, in exactly this shape:
+{"commits": [{"hunks": [1, 3], "message": "feat(api): add the login endpoint"}]}
+
+- list the commits in the order they should be committed
+- every hunk belongs to exactly one commit
+- write the messages in the {style} commit style and in {language}
+- use a single commit when the changes belong together"#;
+
+/// Lines per hunk tried in turn until the listing for the model fits the token budget
+const LINE_LIMITS: [usize; 5] = [200, 100, 50, 20, 10];
+
+#[derive(Debug, Deserialize)]
+struct Plan {
+    commits: Vec<Group>,
+}
+
+/// The hunks of one planned commit
+#[derive(Debug, Deserialize)]
+struct Group {
+    hunks: Vec<usize>,
+    message: String,
+}
+
+/// Let the model split the staged changes into atomic commits and commit them one after the other
+pub fn handler(options: ai::Options) -> Result<()> {
+    if git_rev_parse("HEAD").is_none() {
+        return Err(anyhow!("Splitting needs a commit to start from"));
+    }
+    let files = patch::parse(&git_stage_patch());
+    let hunks = patch::hunks(&files);
+    if hunks.is_empty() {
+        return Err(anyhow!("No changes staged"));
+    }
+
+    let repo_config = RepoConfig::load()?;
+    let style = options.style.or(repo_config.style);
+    let rules = ai::configured_rules(&options, &repo_config, style)?;
+    let language = ai::configured_language(options.language.clone(), &repo_config);
+    let language = language::normalize(language.as_deref().unwrap_or("English"));
+    let style_name = style
+        .and_then(|style| style.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .unwrap_or("conventional".to_string());
+    let system_prompt = SPLIT_PROMPT
+        .replace("{style}", &style_name)
+        .replace("{language}", &language);
+    let listing = listing(&files, &hunks);
+
+    let groups = loop {
+        let result = llm::request_with_progress(
+            &format!("Planning commits for {} hunks", hunks.len()),
+            &[ChatMessage::system(&system_prompt), ChatMessage::user(&listing)],
+            options.vendor,
+            options.model.clone(),
+        )?;
+
+        let mut groups = parse_plan(result.message(), hunks.len())?;
+        for group in groups.iter_mut() {
+            group.message = lint::fix(&group.message, &rules, style);
+        }
+        print_plan(&files, &hunks, &groups);
+        if options.dry_run {
+            return Ok(());
+        }
+
+        let answer = ask(&format!(
+            "Commit this plan? {}es  {}egenerate  {}o (Y) ",
+            "[y]".bold(),
+            "[r]".bold(),
+            "[n]".bold()
+        ));
+        match answer.as_deref().map(|a| a.to_lowercase()).as_deref() {
+            Some("" | "y") => break groups,
+            Some("r") => continue,
+            _ => {
+                println!("{}", "Cancel split".red());
+                return Ok(());
+            }
+        }
+    };
+
+    let commits: Vec<(Vec<Hunk>, String)> = groups
+        .iter()
+        .map(|group| {
+            let selected = group.hunks.iter().map(|id| hunks[id - 1]).collect();
+            (selected, group.message.trim().to_string())
+        })
+        .collect();
+    commit_hunks(&files, &commits, true)?;
+
+    println!("{} into {} commits", "Split success!!!".green().bold(), groups.len());
+    Ok(())
+}
+
+/// Commits the hunks of each entry on top of HEAD, in order. Staged hunks no entry lists stay staged, with
+/// `complete` every staged hunk must be committed. On failure the branch and the index are restored to where
+/// they were.
+pub(crate) fn commit_hunks(files: &[FilePatch], commits: &[(Vec<Hunk>, String)], complete: bool) -> Result<()> {
+    let head = git_rev_parse("HEAD").ok_or_else(|| anyhow!("HEAD does not point to a commit"))?;
+    let index = git_write_tree().ok_or_else(|| anyhow!("Can not save the index"))?;
+
+    let result = commit_each(files, commits)
+        .and_then(|_| {
+            // anything else than the saved index means the patches did not add up to the staged changes
+            if complete && git_write_tree().as_deref() != Some(index.as_str()) {
+                return Err(anyhow!("The commits do not match the staged changes"));
+            }
+            Ok(())
+        })
+        // the saved index is the new HEAD plus the hunks that were not committed
+        .and_then(|_| git_read_tree(&index));
+    if let Err(e) = result {
+        let restored = git_reset_soft(&head, false).and_then(|_| git_read_tree(&index));
+        return match restored {
+            Ok(_) => Err(anyhow!("{}, the branch and the index are restored", e)),
+            Err(restore) => Err(anyhow!(
+                "{}, restoring failed too: {}. Run `git reset --soft {}` and `git read-tree {}` to restore",
+                e,
+                restore,
+                head,
+                index
+            )),
+        };
+    }
+    Ok(())
+}
+
+fn commit_each(files: &[FilePatch], commits: &[(Vec<Hunk>, String)]) -> Result<()> {
+    git_read_tree("HEAD")?;
+    for (n, (hunks, message)) in commits.iter().enumerate() {
+        git_apply_cached(&patch::build(files, hunks))?;
+        git_commit(message, false)?;
+        println!(
+            "{} {}",
+            format!("[{}/{}]", n + 1, commits.len()).bold(),
+            header(message)
+        );
+    }
+    Ok(())
+}
+
+/// The hunks for the model, cut shorter until they fit the token budget
+fn listing(files: &[FilePatch], hunks: &[Hunk]) -> String {
+    let render = |max_lines| {
+        hunks
+            .iter()
+            .map(|hunk| patch::describe(files, hunk, max_lines))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut text = render(usize::MAX);
+    for max_lines in LINE_LIMITS {
+        if estimate_tokens(&text) <= DEFAULT_DIFF_TOKENS {
+            break;
+        }
+        text = render(max_lines);
+    }
+    text
+}
+
+/// Parses the JSON answer and checks that every hunk is committed exactly once. Hunks the model forgot go to
+/// the last commit, repeated hunks stay in the first commit listing them.
+fn parse_plan(answer: &str, count: usize) -> Result<Vec<Group>> {
+    let json = llm::extract_json(answer)?;
+    let plan: Plan = serde_json::from_str(json).map_err(|e| anyhow!("Can not parse the plan: {}\n{}", e, answer))?;
+
+    let mut seen = HashSet::new();
+    let mut groups = vec![];
+    for mut group in plan.commits {
+        if let Some(id) = group.hunks.iter().find(|id| **id == 0 || **id > count) {
+            return Err(anyhow!("The plan refers to hunk {} of {}", id, count));
+        }
+        let mut listed = HashSet::new();
+        group.hunks.retain(|id| !seen.contains(id) && listed.insert(*id));
+        if !group.hunks.is_empty() && !group.message.trim().is_empty() {
+            seen.extend(group.hunks.iter().copied());
+            groups.push(group);
+        }
+    }
+
+    let forgotten: Vec<usize> = (1..=count).filter(|id| !seen.contains(id)).collect();
+    match groups.last_mut() {
+        Some(last) => last.hunks.extend(forgotten),
+        None => return Err(anyhow!("The plan has no commits")),
+    }
+    Ok(groups)
+}
+
+fn print_plan(files: &[FilePatch], hunks: &[Hunk], groups: &[Group]) {
+    for (n, group) in groups.iter().enumerate() {
+        println!(
+            "\n{} {}",
+            format!("Commit {}:", n + 1).bold(),
+            header(&group.message).green()
+        );
+        for id in &group.hunks {
+            println!("    {}", patch::label(files, &hunks[id - 1]).truecolor(128, 128, 128));
+        }
+    }
+    println!();
+}
+
+fn header(message: &str) -> &str {
+    message.trim().lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan() {
+        let answer = r#"```json
+{"commits": [
+  {"hunks": [2], "message": "fix: handle empty input"},
+  {"hunks": [1, 2], "message": "feat: add logging"}
+]}
+```"#;
+        let groups = parse_plan(answer, 4).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].hunks, vec![2]);
+        assert_eq!(groups[1].hunks, vec![1, 3, 4]);
+
+        let groups = parse_plan(
+            r#"{"commits": [{"hunks": [1, 2, 1], "message": "feat: a"}, {"hunks": [3], "message": ""}]}"#,
+            3,
+        )
+        .unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].hunks, vec![1, 2, 3]);
+
+        assert!(parse_plan(r#"{"commits": [{"hunks": [5], "message": "x"}]}"#, 4).is_err());
+        assert!(parse_plan(r#"{"commits": []}"#, 4).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+/// The staged diff of one file
+#[derive(Debug)]
+pub struct FilePatch {
+    pub path: String,
+    /// everything before the first hunk: `diff --git`, mode and index lines, `---`/`+++`, binary patches
+    pub header: String,
+    /// the `@@` hunks, each with its lines
+    pub hunks: Vec<String>,
+}
+
+impl FilePatch {
+    /// Plain modifications can be committed hunk by hunk. New, deleted and binary files and mode changes are
+    /// kept whole, a second patch for the same file would repeat the header and fail to apply.
+    fn splittable(&self) -> bool {
+        !self.hunks.is_empty()
+            && !self.header.lines().any(|line| {
+                [
+                    "new file",
+                    "deleted file",
+                    "old mode",
+                    "new mode",
+                    "Binary files",
+                    "GIT binary patch",
+                ]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+            })
+    }
+
+    fn patch(&self, hunk: Option<usize>) -> String {
+        match hunk {
+            Some(n) => format!("{}{}", self.header, self.hunks[n]),
+            None => format!("{}{}", self.header, self.hunks.concat()),
+        }
+    }
+}
+
+/// A part of the staged diff that can be committed on its own, numbered from 1 for the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hunk {
+    pub id: usize,
+    /// index of the file in the parsed diff
+    pub file: usize,
+    /// index of the hunk in the file, `None` for a file that is committed whole
+    pub hunk: Option<usize>,
+}
+
+/// Splits a `git diff` into files and hunks
+pub fn parse(diff: &str) -> Vec<FilePatch> {
+    let mut files: Vec<FilePatch> = vec![];
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FilePatch {
+                path: path_of(line),
+                header: line.to_string(),
+                hunks: vec![],
+            });
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else { continue };
+        if line.starts_with("@@") {
+            file.hunks.push(line.to_string());
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.push_str(line);
+        } else {
+            if let Some(path) = line.strip_prefix("+++ b/") {
+                file.path = path.trim_end().to_string();
+            }
+            file.header.push_str(line);
+        }
+    }
+    files
+}
+
+/// Numbers the parts of the files that can be committed separately
+pub fn hunks(files: &[FilePatch]) -> Vec<Hunk> {
+    let mut hunks = vec![];
+    for (n, file) in files.iter().enumerate() {
+        let parts: Vec<Option<usize>> = if file.splittable() {
+            (0..file.hunks.len()).map(Some).collect()
+        } else {
+            vec![None]
+        };
+        for hunk in parts {
+            hunks.push(Hunk {
+                id: hunks.len() + 1,
+                file: n,
+                hunk,
+            });
+        }
+    }
+    hunks
+}
+
+/// Builds a patch of the given hunks, in the order of the original diff
+pub fn build(files: &[FilePatch], hunks: &[Hunk]) -> String {
+    let mut hunks = hunks.to_vec();
+    hunks.sort_by_key(|hunk| hunk.id);
+
+    let mut patch = String::new();
+    for (n, file) in files.iter().enumerate() {
+        let selected: Vec<&Hunk> = hunks.iter().filter(|hunk| hunk.file == n).collect();
+        match selected.as_slice() {
+            [] => {}
+            [whole] if whole.hunk.is_none() => patch.push_str(&file.patch(None)),
+            selected => {
+                patch.push_str(&file.header);
+                for hunk in selected.iter().filter_map(|hunk| hunk.hunk) {
+                    patch.push_str(&file.hunks[hunk]);
+                }
+            }
+        }
+    }
+    patch
+}
+
+/// Renders a hunk for the model, cutting it after `max_lines` lines
+pub fn describe(files: &[FilePatch], hunk: &Hunk, max_lines: usize) -> String {
+    let file = &files[hunk.file];
+    let text = match hunk.hunk {
+        Some(n) => file.hunks[n].clone(),
+        None => file.patch(None),
+    };
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut description = format!("### Hunk {}: {}\n", hunk.id, file.path);
+    for line in lines.iter().take(max_lines) {
+        description.push_str(line);
+        description.push('\n');
+    }
+    if lines.len() > max_lines {
+        description.push_str(&format!("[... {} more lines omitted]\n", lines.len() - max_lines));
+    }
+    description
+}
+
+/// Short label of a hunk for the plan, the file and the `@@` line
+pub fn label(files: &[FilePatch], hunk: &Hunk) -> String {
+    let file = &files[hunk.file];
+    match hunk.hunk {
+        Some(n) => format!("{} {}", file.path, file.hunks[n].lines().next().unwrap_or_default()),
+        None => file.path.clone(),
+    }
+}
+
+/// Lines of the old file a hunk changes: the removed lines, or for pure additions the context lines around
+/// them. `None` for a file that is committed whole.
+pub fn touched_lines(files: &[FilePatch], hunk: &Hunk) -> Option<Vec<usize>> {
+    let text = &files[hunk.file].hunks[hunk.hunk?];
+    let mut lines = text.lines();
+    // "@@ -12,7 +12,8 @@ fn main() {", the count is left out for one line hunks
+    let start: usize = lines
+        .next()?
+        .strip_prefix("@@ -")?
+        .split([',', ' '])
+        .next()?
+        .parse()
+        .ok()?;
+
+    let mut removed = vec![];
+    let mut around = vec![];
+    let mut line = start;
+    let mut previous = ' ';
+    for text in lines {
+        match text.chars().next() {
+            Some('-') => {
+                removed.push(line);
+                line += 1;
+            }
+            Some('+') => {
+                if previous == ' ' && line > start {
+                    around.push(line - 1);
+                }
+            }
+            Some(' ') => {
+                if previous == '+' {
+                    around.push(line);
+                }
+                line += 1;
+            }
+            // "\ No newline at end of file"
+            _ => continue,
+        }
+        previous = text.chars().next().unwrap_or(' ');
+    }
+
+    let mut touched = if removed.is_empty() { around } else { removed };
+    touched.dedup();
+    Some(touched)
+}
+
+fn path_of(line: &str) -> String {
+    line.trim_end()
+        .rsplit_once(" b/")
+        .map(|(_, path)| path.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,4 @@
+ fn main() {
++    setup();
+     run();
+ }
+@@ -20,2 +21,3 @@ fn run() {
+     work();
++    log();
+ }
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+";
+
+    #[test]
+    fn test_parse_and_build() {
+        let files = parse(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/main.rs");
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[1].path, "logo.png");
+
+        let hunks = hunks(&files);
+        assert_eq!(hunks.len(), 3);
+        assert_eq!(hunks[2].hunk, None);
+        assert_eq!(label(&files, &hunks[1]), "src/main.rs @@ -20,2 +21,3 @@ fn run() {");
+
+        let patch = build(&files, &[hunks[1]]);
+        assert!(patch.starts_with("diff --git a/src/main.rs b/src/main.rs\nindex"));
+        assert!(patch.contains("+    log();"));
+        assert!(!patch.contains("setup"));
+        assert!(!patch.contains("logo.png"));
+
+        assert_eq!(build(&files, &hunks), DIFF);
+        assert!(describe(&files, &hunks[0], 2).ends_with("[... 3 more lines omitted]\n"));
+
+        assert_eq!(touched_lines(&files, &hunks[0]), Some(vec![1, 2]));
+        assert_eq!(touched_lines(&files, &hunks[1]), Some(vec![20, 21]));
+        assert_eq!(touched_lines(&files, &hunks[2]), None);
+    }
+}
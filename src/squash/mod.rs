@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai;
+use crate::ai::editor;
+use crate::ai::git::{
+    change_summary, git_commit, git_log_oneline, git_merge_base, git_range_changes, git_range_diff,
+    git_remote_branches_containing, git_reset_soft, git_rev_parse, git_stage_filenames,
+};
+use crate::ai::menu::ask;
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+/// Generate one message for all commits of the branch since `base`, and squash them into it with `commit`
+pub fn handler(base: &str, commit: bool, options: ai::Options) -> Result<()> {
+    let merge_base = git_merge_base(base, "HEAD").ok_or_else(|| anyhow!("No common ancestor with `{}`", base))?;
+    let commits = git_log_oneline(&merge_base, "HEAD");
+    if commits.is_empty() {
+        return Err(anyhow!("No commits between `{}` and HEAD", base));
+    }
+    if commit && !git_stage_filenames().is_empty() {
+        return Err(anyhow!(
+            "There are staged changes, commit or unstage them before squashing"
+        ));
+    }
+
+    let changes = git_range_changes(&merge_base, "HEAD");
+    let filenames: Vec<String> = changes.iter().map(|change| change.path.clone()).collect();
+    let diff = branch_context(
+        &commits,
+        &format!(
+            "{}\n{}",
+            change_summary(&changes),
+            truncate_diff(&git_range_diff(&merge_base, "HEAD"), DEFAULT_DIFF_TOKENS)
+        ),
+    );
+
+    println!("Squashing {} commits since `{}`", commits.len(), base);
+    let mut message = ai::generate_message_for(&options, filenames.clone(), diff.clone())?;
+    if !commit {
+        println!("{}", message);
+        return Ok(());
+    }
+
+    let pushed = git_remote_branches_containing("HEAD");
+    if !pushed.is_empty() {
+        println!(
+            "{} HEAD is already on {}, squashing rewrites published history",
+            "warning:".yellow().bold(),
+            pushed.join(", ")
+        );
+    }
+
+    loop {
+        println!("{}", message.bold());
+        let answer = ask(&format!(
+            "Squash the branch into this commit? {}es  {}dit  {}egenerate  {}o (Y) ",
+            "[y]".bold(),
+            "[e]".bold(),
+            "[r]".bold(),
+            "[n]".bold()
+        ));
+        match answer.as_deref().map(|a| a.to_lowercase()).as_deref() {
+            Some("" | "y") => break,
+            Some("e") => match editor::edit(&message, &filenames) {
+                Ok(edited) if !edited.is_empty() => message = edited,
+                Ok(_) => println!("Empty message, keeping the generated one"),
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("r") => match ai::generate_message_for(&options, filenames.clone(), diff.clone()) {
+                Ok(generated) => message = generated,
+                Err(e) => eprintln!("{}", e),
+            },
+            Some("n") | None => {
+                println!("{}", "Cancel squash".red());
+                return Ok(());
+            }
+            Some(other) => println!("Unknown option `{}`", other),
+        }
+    }
+
+    let head = git_rev_parse("HEAD").ok_or_else(|| anyhow!("HEAD does not point to a commit"))?;
+    git_reset_soft(&merge_base, options.dry_run)?;
+    if let Err(e) = git_commit(message.trim(), options.dry_run) {
+        return Err(anyhow!(
+            "{}, the changes are staged. Run `git reset --soft {}` to get the commits back",
+            e,
+            head
+        ));
+    }
+    println!("{}", "Squash success!!!".green().bold());
+    Ok(())
+}
+
+/// Puts the commit subjects of the branch in front of the net diff, they tell the model what the branch is about
+fn branch_context(commits: &[String], diff: &str) -> String {
+    let commits: Vec<String> = commits.iter().map(|commit| format!("- {}", commit)).collect();
+    format!(
+        "The changes squash these commits of the branch:\n{}\n\n{}",
+        commits.join("\n"),
+        diff
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_branch_context() {
+        let commits = vec!["a1b2c3d wip".to_string(), "e4f5a6b fix tests".to_string()];
+        let context = branch_context(&commits, "diff --git a/x b/x");
+
+        assert_eq!(
+            context,
+            "The changes squash these commits of the branch:\n- a1b2c3d wip\n- e4f5a6b fix tests\n\ndiff --git a/x b/x"
+        );
+    }
+}
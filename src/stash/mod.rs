@@ -0,0 +1,98 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai::git::{change_summary, git_stash_push, git_untracked_files, git_worktree_changes, git_worktree_diff};
+use crate::llm;
+use crate::llm::{ChatMessage, PromptModel};
+use crate::prompt::budget::{truncate_diff, DEFAULT_DIFF_TOKENS};
+
+const STASH_PROMPT: &str = r#"You label git stashes so they can be found again later. Describe the work in progress
+of the diff in one short line: at most 60 characters, English, no trailing period, no quotes, no commit type prefix.
+Output ONLY the line."#;
+
+const MAX_MESSAGE_LENGTH: usize = 72;
+
+/// Options of `gitbuddy stash`
+pub struct Options {
+    /// also stash untracked files
+    pub include_untracked: bool,
+    /// only stash the changes under these pathspecs
+    pub paths: Vec<String>,
+    /// only show the message
+    pub dry_run: bool,
+    pub vendor: Option<PromptModel>,
+    pub model: Option<String>,
+}
+
+/// Stash the local changes with a generated description instead of "WIP on <branch>"
+pub fn handler(options: Options) -> Result<()> {
+    let diff = git_worktree_diff(&options.paths);
+    let untracked = if options.include_untracked {
+        git_untracked_files(&options.paths)
+    } else {
+        vec![]
+    };
+    if !diff.contains("diff --git") && untracked.is_empty() {
+        return Err(anyhow!("No local changes to stash"));
+    }
+
+    let mut input = format!(
+        "{}\n{}",
+        change_summary(&git_worktree_changes(&options.paths)),
+        truncate_diff(&diff, DEFAULT_DIFF_TOKENS)
+    );
+    if !untracked.is_empty() {
+        input.push_str(&format!("\nNew untracked files:\n{}\n", untracked.join("\n")));
+    }
+
+    let result = llm::request_with_progress(
+        "Describing the stash",
+        &[ChatMessage::system(STASH_PROMPT), ChatMessage::user(&input)],
+        options.vendor,
+        options.model.clone(),
+    )?;
+
+    let message = clean_message(result.message());
+    if message.is_empty() {
+        return Err(anyhow!("The model returned an empty description"));
+    }
+
+    git_stash_push(&message, options.include_untracked, &options.paths, options.dry_run)?;
+    if options.dry_run {
+        println!("{}", message);
+    } else {
+        println!("{} {}", "Stashed".green().bold(), message);
+    }
+    Ok(())
+}
+
+/// Keeps the first line of the answer without the quotes and punctuation models like to add
+fn clean_message(answer: &str) -> String {
+    let line = answer.trim().lines().next().unwrap_or_default();
+    let line = line.trim().trim_matches(|c| c == '"' || c == '\'' || c == '`').trim();
+    let line = line.trim_end_matches('.');
+    if line.chars().count() <= MAX_MESSAGE_LENGTH {
+        return line.to_string();
+    }
+
+    let cut: String = line.chars().take(MAX_MESSAGE_LENGTH).collect();
+    match cut.rsplit_once(' ') {
+        Some((words, _)) => words.to_string(),
+        None => cut,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clean_message() {
+        assert_eq!(
+            clean_message("\"Half-done retry logic for the uploader.\"\nIt adds backoff."),
+            "Half-done retry logic for the uploader"
+        );
+        assert_eq!(clean_message("`wip login form`"), "wip login form");
+        assert!(clean_message(&"word ".repeat(30)).chars().count() <= MAX_MESSAGE_LENGTH);
+    }
+}
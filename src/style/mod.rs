@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::ai::git::git_log_messages;
+use crate::commit::CommitMessage;
+
+pub mod preset;
+
+/// Print the commit conventions detected from the recent history of the repository
+pub fn analyze_handler(count: usize, paths: &[String]) -> Result<()> {
+    let messages = git_log_messages(count, paths);
+    if messages.is_empty() {
+        return Err(anyhow!("No commits found to analyze"));
+    }
+
+    print!("{}", StyleReport::analyze(&messages));
+    Ok(())
+}
+
+/// Conventions detected from a set of commit messages
+#[derive(Debug, Default)]
+pub struct StyleReport {
+    pub total: usize,
+    pub conventional: usize,
+    pub types: Vec<(String, usize)>,
+    pub scopes: Vec<(String, usize)>,
+    pub average_subject_length: usize,
+    pub capitalized: usize,
+    pub trailing_period: usize,
+    pub with_body: usize,
+    pub ticket_references: usize,
+    pub trailers: Vec<(String, usize)>,
+}
+
+impl StyleReport {
+    pub fn analyze(messages: &[String]) -> Self {
+        let mut report = StyleReport {
+            total: messages.len(),
+            ..Default::default()
+        };
+
+        let mut types = HashMap::new();
+        let mut scopes = HashMap::new();
+        let mut trailers = HashMap::new();
+        let mut subject_length = 0;
+
+        for message in messages {
+            let message = CommitMessage::parse(message);
+
+            if let Some(conventional) = message.conventional() {
+                report.conventional += 1;
+                *types.entry(conventional.kind).or_insert(0) += 1;
+                if let Some(scope) = conventional.scope {
+                    *scopes.entry(scope).or_insert(0) += 1;
+                }
+            }
+
+            let subject = message.subject();
+            subject_length += subject.chars().count();
+            if subject.chars().next().is_some_and(|c| c.is_uppercase()) {
+                report.capitalized += 1;
+            }
+            if subject.ends_with('.') {
+                report.trailing_period += 1;
+            }
+            if message.body.is_some() {
+                report.with_body += 1;
+            }
+            if message.header.split_whitespace().any(is_ticket_reference) {
+                report.ticket_references += 1;
+            }
+            for (token, _) in message.footers {
+                *trailers.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        report.average_subject_length = subject_length / report.total.max(1);
+        report.types = sorted_by_count(types);
+        report.scopes = sorted_by_count(scopes);
+        report.trailers = sorted_by_count(trailers);
+        report
+    }
+}
+
+impl Display for StyleReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let percent = |n: usize| n * 100 / self.total.max(1);
+        let counts = |items: &[(String, usize)]| {
+            if items.is_empty() {
+                "-".to_string()
+            } else {
+                items
+                    .iter()
+                    .map(|(name, n)| format!("{} ({})", name, n))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+
+        writeln!(f, "{}", format!("Analyzed {} commits", self.total).bold())?;
+        writeln!(
+            f,
+            "Conventional commits:   {}% ({}/{})",
+            percent(self.conventional),
+            self.conventional,
+            self.total
+        )?;
+        writeln!(f, "Types:                  {}", counts(&self.types))?;
+        writeln!(f, "Scopes:                 {}", counts(&self.scopes))?;
+        writeln!(f, "Average subject length: {} chars", self.average_subject_length)?;
+        writeln!(f, "Capitalized subjects:   {}%", percent(self.capitalized))?;
+        writeln!(f, "Trailing period:        {}%", percent(self.trailing_period))?;
+        writeln!(f, "With body:              {}%", percent(self.with_body))?;
+        writeln!(f, "Ticket references:      {}%", percent(self.ticket_references))?;
+        writeln!(f, "Trailers:               {}", counts(&self.trailers))
+    }
+}
+
+fn sorted_by_count(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut items: Vec<(String, usize)> = counts.into_iter().collect();
+    items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    items
+}
+
+/// Matches ticket ids such as `#123`, `PROJ-123` or `[PROJ-123]`
+fn is_ticket_reference(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '#' && c != '-');
+    if let Some(number) = word.strip_prefix('#') {
+        return !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+    }
+
+    match word.split_once('-') {
+        Some((project, number)) => {
+            !project.is_empty()
+                && project.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                && project.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        let messages: Vec<String> = [
+            "feat(llm): add ollama support",
+            "fix(llm): handle empty choices\n\nSigned-off-by: A <a@example.com>",
+            "fix: typo in README.",
+            "Update dependencies for PROJ-42",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let report = StyleReport::analyze(&messages);
+        assert_eq!(report.total, 4);
+        assert_eq!(report.conventional, 3);
+        assert_eq!(report.types[0], ("fix".to_string(), 2));
+        assert_eq!(report.scopes, vec![("llm".to_string(), 2)]);
+        assert_eq!(report.capitalized, 1);
+        assert_eq!(report.trailing_period, 1);
+        assert_eq!(report.ticket_references, 1);
+        assert_eq!(report.trailers, vec![("Signed-off-by".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_ticket_reference() {
+        assert!(is_ticket_reference("#12"));
+        assert!(is_ticket_reference("[PROJ-123]"));
+        assert!(!is_ticket_reference("add-on"));
+        assert!(!is_ticket_reference("#"));
+    }
+}
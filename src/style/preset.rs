@@ -0,0 +1,576 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::git::git_config;
+use crate::commit::rules::{Rules, Violation};
+use crate::commit::CommitMessage;
+
+/// Commit message styles, each bundling a prompt, a validator and a formatter
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// Conventional Commits: `<type>(<scope>): <subject>`
+    Conventional,
+    /// Angular commit guidelines, a stricter Conventional Commits flavour
+    Angular,
+    /// gitmoji: `<emoji> <subject>`
+    Gitmoji,
+    /// Linux kernel: `subsystem: summary`, wrapped body and Signed-off-by
+    Kernel,
+    /// Plain imperative English subject line
+    Plain,
+}
+
+const CONVENTIONAL_TYPES: [&str; 11] = [
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+];
+
+const ANGULAR_TYPES: [&str; 8] = ["build", "ci", "docs", "feat", "fix", "perf", "refactor", "test"];
+
+/// The official gitmoji list: emoji, shortcode and intention
+pub const GITMOJIS: [(&str, &str, &str); 75] = [
+    ("🎨", ":art:", "Improve structure / format of the code."),
+    ("⚡️", ":zap:", "Improve performance."),
+    ("🔥", ":fire:", "Remove code or files."),
+    ("🐛", ":bug:", "Fix a bug."),
+    ("🚑️", ":ambulance:", "Critical hotfix."),
+    ("✨", ":sparkles:", "Introduce new features."),
+    ("📝", ":memo:", "Add or update documentation."),
+    ("🚀", ":rocket:", "Deploy stuff."),
+    ("💄", ":lipstick:", "Add or update the UI and style files."),
+    ("🎉", ":tada:", "Begin a project."),
+    ("✅", ":white_check_mark:", "Add, update, or pass tests."),
+    ("🔒️", ":lock:", "Fix security or privacy issues."),
+    ("🔐", ":closed_lock_with_key:", "Add or update secrets."),
+    ("🔖", ":bookmark:", "Release / Version tags."),
+    ("🚨", ":rotating_light:", "Fix compiler / linter warnings."),
+    ("🚧", ":construction:", "Work in progress."),
+    ("💚", ":green_heart:", "Fix CI Build."),
+    ("⬇️", ":arrow_down:", "Downgrade dependencies."),
+    ("⬆️", ":arrow_up:", "Upgrade dependencies."),
+    ("📌", ":pushpin:", "Pin dependencies to specific versions."),
+    ("👷", ":construction_worker:", "Add or update CI build system."),
+    (
+        "📈",
+        ":chart_with_upwards_trend:",
+        "Add or update analytics or track code.",
+    ),
+    ("♻️", ":recycle:", "Refactor code."),
+    ("➕", ":heavy_plus_sign:", "Add a dependency."),
+    ("➖", ":heavy_minus_sign:", "Remove a dependency."),
+    ("🔧", ":wrench:", "Add or update configuration files."),
+    ("🔨", ":hammer:", "Add or update development scripts."),
+    ("🌐", ":globe_with_meridians:", "Internationalization and localization."),
+    ("✏️", ":pencil2:", "Fix typos."),
+    ("💩", ":poop:", "Write bad code that needs to be improved."),
+    ("⏪️", ":rewind:", "Revert changes."),
+    ("🔀", ":twisted_rightwards_arrows:", "Merge branches."),
+    ("📦️", ":package:", "Add or update compiled files or packages."),
+    ("👽️", ":alien:", "Update code due to external API changes."),
+    (
+        "🚚",
+        ":truck:",
+        "Move or rename resources (e.g.: files, paths, routes).",
+    ),
+    ("📄", ":page_facing_up:", "Add or update license."),
+    ("💥", ":boom:", "Introduce breaking changes."),
+    ("🍱", ":bento:", "Add or update assets."),
+    ("♿️", ":wheelchair:", "Improve accessibility."),
+    ("💡", ":bulb:", "Add or update comments in source code."),
+    ("🍻", ":beers:", "Write code drunkenly."),
+    ("💬", ":speech_balloon:", "Add or update text and literals."),
+    ("🗃️", ":card_file_box:", "Perform database related changes."),
+    ("🔊", ":loud_sound:", "Add or update logs."),
+    ("🔇", ":mute:", "Remove logs."),
+    ("👥", ":busts_in_silhouette:", "Add or update contributor(s)."),
+    ("🚸", ":children_crossing:", "Improve user experience / usability."),
+    ("🏗️", ":building_construction:", "Make architectural changes."),
+    ("📱", ":iphone:", "Work on responsive design."),
+    ("🤡", ":clown_face:", "Mock things."),
+    ("🥚", ":egg:", "Add or update an easter egg."),
+    ("🙈", ":see_no_evil:", "Add or update a .gitignore file."),
+    ("📸", ":camera_flash:", "Add or update snapshots."),
+    ("⚗️", ":alembic:", "Perform experiments."),
+    ("🔍️", ":mag:", "Improve SEO."),
+    ("🏷️", ":label:", "Add or update types."),
+    ("🌱", ":seedling:", "Add or update seed files."),
+    (
+        "🚩",
+        ":triangular_flag_on_post:",
+        "Add, update, or remove feature flags.",
+    ),
+    ("🥅", ":goal_net:", "Catch errors."),
+    ("💫", ":dizzy:", "Add or update animations and transitions."),
+    ("🗑️", ":wastebasket:", "Deprecate code that needs to be cleaned up."),
+    (
+        "🛂",
+        ":passport_control:",
+        "Work on code related to authorization, roles and permissions.",
+    ),
+    ("🩹", ":adhesive_bandage:", "Simple fix for a non-critical issue."),
+    ("🧐", ":monocle_face:", "Data exploration/inspection."),
+    ("⚰️", ":coffin:", "Remove dead code."),
+    ("🧪", ":test_tube:", "Add a failing test."),
+    ("👔", ":necktie:", "Add or update business logic."),
+    ("🩺", ":stethoscope:", "Add or update healthcheck."),
+    ("🧱", ":bricks:", "Infrastructure related changes."),
+    ("🧑‍💻", ":technologist:", "Improve developer experience."),
+    (
+        "💸",
+        ":money_with_wings:",
+        "Add sponsorships or money related infrastructure.",
+    ),
+    (
+        "🧵",
+        ":thread:",
+        "Add or update code related to multithreading or concurrency.",
+    ),
+    ("🦺", ":safety_vest:", "Add or update code related to validation."),
+    ("✈️", ":airplane:", "Improve offline support."),
+    ("🦖", ":t-rex:", "Code that adds backwards compatibility."),
+];
+
+/// gitmoji used when the model answers with a Conventional Commits type instead
+const TYPE_GITMOJIS: [(&str, &str); 11] = [
+    ("feat", "✨"),
+    ("fix", "🐛"),
+    ("docs", "📝"),
+    ("style", "🎨"),
+    ("refactor", "♻️"),
+    ("perf", "⚡️"),
+    ("test", "✅"),
+    ("build", "📦️"),
+    ("ci", "👷"),
+    ("chore", "🔧"),
+    ("revert", "⏪️"),
+];
+
+const KERNEL_PROMPT: &str = r###"You are a Linux kernel developer. Based on the provided code changes, write a commit message in the Linux kernel style:
+<subsystem>: <summary>
+
+<body>
+
+Requirements:
+1. Output ONLY the commit message, no explanations or additional content.
+2. The subsystem is the lower-case area of the code being changed (e.g. "net", "mm", "drivers/usb"), followed by a colon.
+3. The summary is in imperative mood, starts with a lower-case letter, does not end with a period and is at most 75 characters.
+4. The body explains what the problem is and why the change is needed, wrapped at 75 columns.
+5. Do NOT write a Signed-off-by line, it is appended from the git user.name and user.email.
+"###;
+
+const PLAIN_PROMPT: &str = r###"You are a professional Git assistant. Based on the provided code changes, write a commit message following these rules:
+1. Output ONLY the commit message, no explanations or additional content.
+2. The subject line is written in imperative mood ("Add feature", not "Added feature" or "Adds feature").
+3. The subject line starts with a capital letter, does not end with a period and is at most 50 characters.
+4. Do NOT use prefixes such as "feat:" or "fix:".
+5. If the change needs explaining, add a body after a blank line, wrapped at 72 columns, explaining what and why.
+"###;
+
+impl Preset {
+    /// System prompt of the style
+    pub fn prompt(self) -> String {
+        match self {
+            Preset::Conventional => format!(
+                "You are a professional Git assistant. Based on the provided code changes, write a commit message \
+                 following the Conventional Commits specification:\n\
+                 <type>[optional scope][!]: <subject>\n\n[optional body]\n\n[optional footer(s)]\n\n\
+                 Requirements:\n\
+                 1. Output ONLY the commit message, no explanations or additional content.\n\
+                 2. The type must be one of: {}.\n\
+                 3. Mark breaking changes with `!` and a `BREAKING CHANGE:` footer.\n\
+                 4. The subject is in imperative mood and at most 72 characters.\n",
+                CONVENTIONAL_TYPES.join(", ")
+            ),
+            Preset::Angular => format!(
+                "You are a professional Git assistant. Based on the provided code changes, write a commit message \
+                 following the Angular commit message guidelines:\n\
+                 <type>(<scope>): <short summary>\n\n<body>\n\n<footer>\n\n\
+                 Requirements:\n\
+                 1. Output ONLY the commit message, no explanations or additional content.\n\
+                 2. The type must be one of: {}.\n\
+                 3. The summary is in present tense imperative mood, not capitalized, with no period at the end.\n\
+                 4. The whole header is at most 100 characters.\n\
+                 5. The body explains the motivation for the change.\n\
+                 6. Breaking changes are described in a `BREAKING CHANGE:` footer.\n",
+                ANGULAR_TYPES.join(", ")
+            ),
+            Preset::Gitmoji => {
+                let gitmojis: Vec<String> = GITMOJIS
+                    .iter()
+                    .map(|(emoji, _, intention)| format!("{} {}", emoji, intention))
+                    .collect();
+                format!(
+                    "You are a professional Git assistant. Based on the provided code changes, write a commit \
+                     message following the gitmoji convention:\n\
+                     <emoji> <subject>\n\n[optional body]\n\n\
+                     Requirements:\n\
+                     1. Output ONLY the commit message, no explanations or additional content.\n\
+                     2. Start with exactly one emoji (the unicode character, not the shortcode) matching the \
+                     intention of the change, chosen from this list:\n{}\n\
+                     3. The subject is in imperative mood and at most 72 characters.\n",
+                    gitmojis.join("\n")
+                )
+            }
+            Preset::Kernel => KERNEL_PROMPT.to_string(),
+            Preset::Plain => PLAIN_PROMPT.to_string(),
+        }
+    }
+
+    /// Whether the style has Conventional Commits scopes that can be inferred from paths
+    pub fn uses_scopes(self) -> bool {
+        matches!(self, Preset::Conventional | Preset::Angular)
+    }
+
+    /// Rules of the style expressible by the shared commit rules
+    pub fn rules(self) -> Rules {
+        match self {
+            Preset::Conventional => Rules {
+                types: CONVENTIONAL_TYPES.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            },
+            Preset::Angular => Rules {
+                types: ANGULAR_TYPES.iter().map(|s| s.to_string()).collect(),
+                header_max_length: Some(100),
+                subject_full_stop: true,
+                ..Default::default()
+            },
+            Preset::Gitmoji => Rules {
+                header_max_length: Some(100),
+                ..Default::default()
+            },
+            Preset::Kernel => Rules {
+                header_max_length: Some(75),
+                body_max_line_length: Some(75),
+                subject_full_stop: true,
+                ..Default::default()
+            },
+            Preset::Plain => Rules {
+                header_max_length: Some(72),
+                subject_full_stop: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Checks specific to the style, on top of [`Preset::rules`]
+    pub fn validate(self, message: &str) -> Vec<Violation> {
+        let mut violations = vec![];
+        let parsed = CommitMessage::parse(message);
+
+        match self {
+            Preset::Conventional | Preset::Angular => {
+                if parsed.conventional().is_none() {
+                    violations.push(Violation {
+                        rule: "header-format",
+                        message: format!("header `{}` is not `<type>(<scope>): <subject>`", parsed.header),
+                    });
+                }
+                if self == Preset::Angular && starts_capitalized(parsed.subject()) {
+                    violations.push(Violation {
+                        rule: "subject-case",
+                        message: "summary must not be capitalized".to_string(),
+                    });
+                }
+            }
+            Preset::Gitmoji => {
+                if gitmoji_prefix(&parsed.header).is_none() {
+                    violations.push(Violation {
+                        rule: "gitmoji",
+                        message: "header must start with an emoji of the gitmoji list".to_string(),
+                    });
+                }
+            }
+            Preset::Kernel => {
+                match parsed.header.split_once(": ") {
+                    Some((subsystem, _)) if !subsystem.is_empty() && !subsystem.contains(' ') => {}
+                    _ => violations.push(Violation {
+                        rule: "header-format",
+                        message: format!("header `{}` is not `subsystem: summary`", parsed.header),
+                    }),
+                }
+                if !parsed.footers.iter().any(|(token, _)| token == "Signed-off-by") {
+                    violations.push(Violation {
+                        rule: "signed-off-by",
+                        message: "missing Signed-off-by trailer".to_string(),
+                    });
+                }
+            }
+            Preset::Plain => {
+                if parsed.conventional().is_some() {
+                    violations.push(Violation {
+                        rule: "header-format",
+                        message: "subject must not have a type prefix".to_string(),
+                    });
+                }
+                if parsed.header.starts_with(|c: char| c.is_lowercase()) && !starts_with_cased_word(&parsed.header) {
+                    violations.push(Violation {
+                        rule: "subject-case",
+                        message: "subject must start with a capital letter".to_string(),
+                    });
+                }
+                if let Some(word) = parsed.subject().split_whitespace().next() {
+                    if !is_imperative(word) {
+                        violations.push(Violation {
+                            rule: "subject-mood",
+                            message: format!("subject should be imperative, not `{}`", word),
+                        });
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Normalizes a generated message into the style
+    pub fn format(self, message: &str) -> String {
+        let message = message.trim();
+        let (header, body) = match message.split_once('\n') {
+            Some((header, body)) => (header.trim().to_string(), body.trim().to_string()),
+            None => (message.to_string(), String::new()),
+        };
+
+        let header = match self {
+            Preset::Conventional | Preset::Angular => match CommitMessage::parse(&header).conventional() {
+                Some(mut conventional) => {
+                    conventional.kind = conventional.kind.to_lowercase();
+                    if self == Preset::Angular {
+                        conventional.description = lowercase_first(&conventional.description);
+                    }
+                    conventional.header()
+                }
+                None => header,
+            },
+            Preset::Gitmoji => gitmoji_header(&header),
+            Preset::Kernel => header,
+            Preset::Plain => uppercase_first(header.trim_end_matches('.')),
+        };
+
+        let width = match self {
+            Preset::Kernel => 75,
+            _ => 72,
+        };
+        let mut message = if body.is_empty() {
+            header
+        } else {
+            format!("{}\n\n{}", header, wrap(&body, width))
+        };
+
+        if self == Preset::Kernel {
+            if let (Some(name), Some(email)) = (git_config("user.name"), git_config("user.email")) {
+                message = sign_off(&message, &format!("{} <{}>", name, email));
+            }
+        }
+
+        message
+    }
+}
+
+/// The gitmoji the header starts with, accepting both the emoji and its shortcode
+fn gitmoji_prefix(header: &str) -> Option<&'static str> {
+    // variation selectors are optional in practice, compare without them
+    let header = header.replace('\u{fe0f}', "");
+    GITMOJIS
+        .iter()
+        .find(|(emoji, code, _)| header.starts_with(&emoji.replace('\u{fe0f}', "")) || header.starts_with(code))
+        .map(|(emoji, _, _)| *emoji)
+}
+
+/// Turns shortcodes and Conventional Commits types into the matching emoji
+fn gitmoji_header(header: &str) -> String {
+    if let Some((_, code, _)) = GITMOJIS.iter().find(|(_, code, _)| header.starts_with(code)) {
+        let emoji = gitmoji_prefix(code).unwrap_or_default();
+        return format!("{} {}", emoji, header[code.len()..].trim_start());
+    }
+
+    if let Some(conventional) = CommitMessage::parse(header).conventional() {
+        let emoji = if conventional.breaking {
+            Some("💥")
+        } else {
+            TYPE_GITMOJIS
+                .iter()
+                .find(|(kind, _)| *kind == conventional.kind)
+                .map(|(_, emoji)| *emoji)
+        };
+        if let Some(emoji) = emoji {
+            return format!("{} {}", emoji, conventional.description);
+        }
+    }
+
+    header.to_string()
+}
+
+/// Appends a Signed-off-by trailer unless the message already has it
+fn sign_off(message: &str, identity: &str) -> String {
+    let trailer = format!("Signed-off-by: {}", identity);
+    if message.lines().any(|line| line.trim() == trailer) {
+        return message.to_string();
+    }
+
+    let parsed = CommitMessage::parse(message);
+    if parsed.footers.is_empty() {
+        format!("{}\n\n{}", message.trim_end(), trailer)
+    } else {
+        format!("{}\n{}", message.trim_end(), trailer)
+    }
+}
+
+/// Wraps the lines of the text at `width` columns, keeping short lines and the paragraph layout as they are
+fn wrap(text: &str, width: usize) -> String {
+    let mut wrapped = vec![];
+
+    for line in text.lines() {
+        if line.chars().count() <= width {
+            wrapped.push(line.to_string());
+            continue;
+        }
+
+        // keep the indentation of nested items and code, continuation lines of list items go under the item text
+        let leading = &line[..line.len() - line.trim_start().len()];
+        let indent = if line.trim_start().starts_with(['-', '*']) {
+            format!("{}  ", leading)
+        } else {
+            leading.to_string()
+        };
+        let mut current = leading.to_string();
+        for word in line.split_whitespace() {
+            if !current.trim().is_empty() && current.chars().count() + word.chars().count() + 1 > width {
+                wrapped.push(current);
+                current = indent.clone();
+            }
+            if !current.trim().is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        wrapped.push(current);
+    }
+
+    wrapped.join("\n")
+}
+
+/// A rough check that the first word is an imperative verb rather than past tense or third person
+fn is_imperative(word: &str) -> bool {
+    const EXCEPTIONS: [&str; 10] = [
+        "bring", "embed", "feed", "need", "seed", "speed", "string", "shred", "process", "address",
+    ];
+
+    let word = word.to_lowercase();
+    if EXCEPTIONS.contains(&word.as_str()) || word.chars().count() < 4 {
+        return true;
+    }
+    !(word.ends_with("ed") || word.ends_with("ing") || (word.ends_with('s') && !word.ends_with("ss")))
+}
+
+/// Whether the first word has capitals after its first letter, like `README`, `OAuth` or `iOS`, so its case
+/// must be kept as it is
+fn starts_with_cased_word(s: &str) -> bool {
+    s.split_whitespace()
+        .next()
+        .is_some_and(|word| word.chars().skip(1).any(|c| c.is_uppercase()))
+}
+
+/// Whether the text starts with a capitalized ordinary word, acronyms and identifiers do not count
+fn starts_capitalized(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_uppercase()) && !starts_with_cased_word(s)
+}
+
+fn uppercase_first(s: &str) -> String {
+    if starts_with_cased_word(s) {
+        return s.to_string();
+    }
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lowercase_first(s: &str) -> String {
+    if starts_with_cased_word(s) {
+        return s.to_string();
+    }
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gitmoji() {
+        assert_eq!(gitmoji_header(":sparkles: add login page"), "✨ add login page");
+        assert_eq!(gitmoji_header("fix(api): handle timeout"), "🐛 handle timeout");
+        assert_eq!(gitmoji_header("feat!: drop Node 6"), "💥 drop Node 6");
+        assert!(Preset::Gitmoji.validate("✨ add login page").is_empty());
+        assert!(Preset::Gitmoji.validate("⚡ speed up parsing").is_empty());
+        assert_eq!(Preset::Gitmoji.validate("add login page")[0].rule, "gitmoji");
+    }
+
+    #[test]
+    fn test_kernel() {
+        let message = sign_off("net: fix refcount leak\n\nThe socket was never released.", "A <a@b.c>");
+        assert_eq!(
+            message,
+            "net: fix refcount leak\n\nThe socket was never released.\n\nSigned-off-by: A <a@b.c>"
+        );
+        assert!(Preset::Kernel.validate(&message).is_empty());
+        assert_eq!(sign_off(&message, "A <a@b.c>"), message);
+
+        let rules: Vec<&str> = Preset::Kernel.validate("Fix the leak").iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec!["header-format", "signed-off-by"]);
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(Preset::Plain.format("add retry to uploads."), "Add retry to uploads");
+        assert_eq!(Preset::Plain.format("iOS: add retry"), "iOS: add retry");
+        assert!(Preset::Plain.validate("iOS retry for uploads").is_empty());
+        assert!(Preset::Plain.validate("Add retry to uploads").is_empty());
+
+        let rules: Vec<&str> = Preset::Plain
+            .validate("fix: added retries")
+            .iter()
+            .map(|v| v.rule)
+            .collect();
+        assert_eq!(rules, vec!["header-format", "subject-case", "subject-mood"]);
+        assert!(!is_imperative("Adds"));
+        assert!(is_imperative("Address"));
+    }
+
+    #[test]
+    fn test_angular_case() {
+        assert_eq!(
+            Preset::Angular.format("docs: Update the guide"),
+            "docs: update the guide"
+        );
+        assert_eq!(Preset::Angular.format("docs: README typo"), "docs: README typo");
+        assert_eq!(Preset::Angular.format("feat: OAuth login"), "feat: OAuth login");
+        assert!(Preset::Angular.validate("docs: README typo").is_empty());
+        assert_eq!(
+            Preset::Angular.validate("docs: Update the guide")[0].rule,
+            "subject-case"
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let text = "- one two three four five six seven eight nine ten\nshort";
+        assert_eq!(
+            wrap(text, 20),
+            "- one two three four\n  five six seven\n  eight nine ten\nshort"
+        );
+        assert_eq!(
+            wrap("  - one two three four five", 16),
+            "  - one two\n    three four\n    five"
+        );
+        assert_eq!(
+            wrap("    let value = compute();", 16),
+            "    let value =\n    compute();"
+        );
+    }
+}